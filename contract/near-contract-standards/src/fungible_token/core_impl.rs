@@ -1,8 +1,9 @@
 use crate::fungible_token::core::FungibleTokenCore;
-use crate::fungible_token::events::{FtBurn, FtMint, FtTransfer};
+use crate::fungible_token::events::{FtBurn, FtTransfer};
 use crate::fungible_token::resolver::FungibleTokenResolver;
+use crate::fungible_token::tax::{TaxExemptions, TaxPolicy};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::LookupMap;
+use near_sdk::collections::{LookupMap, UnorderedSet};
 use near_sdk::json_types::U128;
 use near_sdk::{
     assert_one_yocto, env, ext_contract, log, require, AccountId, Balance, Gas, IntoStorageKey,
@@ -74,6 +75,63 @@ pub struct FungibleToken {
     pub account_storage_usage: StorageUsage,
 
     pub vault: AccountId,
+
+    /// Tax taken from `ft_transfer` and `ft_transfer_call`.
+    pub tax_policy: TaxPolicy,
+
+    /// Accounts consulted before the tax policy rate is applied.
+    pub tax_exemptions: TaxExemptions,
+
+    /// Registered accounts, `accounts` can't be iterated.
+    pub account_index: UnorderedSet<AccountId>,
+
+    /// Cumulative amount burned.
+    pub total_burned: Balance,
+
+    /// Cumulative amount taken by the transfer tax.
+    pub total_taxed: Balance,
+
+    /// Cumulative amount of tax sent to the vault.
+    pub total_to_vault: Balance,
+
+    /// Cumulative amount of tax sent to this contract for the stakers.
+    pub total_to_stakers: Balance,
+}
+
+/// Layout of [`FungibleToken`] before the transfer tax and the account index were added.
+#[derive(BorshDeserialize)]
+pub struct FungibleTokenV0 {
+    pub accounts: LookupMap<AccountId, Balance>,
+    pub total_supply: Balance,
+    pub account_storage_usage: StorageUsage,
+    pub vault: AccountId,
+}
+
+impl FungibleTokenV0 {
+    /// Converts the token to the current layout, `prefix` must be the one it was created with.
+    /// Accounts registered before the migration are missing from `account_index` until
+    /// they are added with `internal_index_accounts`.
+    pub fn migrate<S>(self, prefix: S) -> FungibleToken
+    where
+        S: IntoStorageKey,
+    {
+        let prefix = prefix.into_storage_key();
+        let mut token = FungibleToken {
+            tax_exemptions: TaxExemptions::new([prefix.as_slice(), b"x"].concat()),
+            account_index: UnorderedSet::new([prefix.as_slice(), b"i"].concat()),
+            accounts: self.accounts,
+            total_supply: self.total_supply,
+            account_storage_usage: self.account_storage_usage,
+            vault: self.vault,
+            tax_policy: TaxPolicy::default(),
+            total_burned: 0,
+            total_taxed: 0,
+            total_to_vault: 0,
+            total_to_stakers: 0,
+        };
+        token.measure_account_storage_usage();
+        token
+    }
 }
 
 impl FungibleToken {
@@ -81,11 +139,20 @@ impl FungibleToken {
     where
         S: IntoStorageKey,
     {
+        let prefix = prefix.into_storage_key();
         let mut this = Self {
+            // No account key can start with `x` or `i`, account ids are at most 64 bytes long
+            tax_exemptions: TaxExemptions::new([prefix.as_slice(), b"x"].concat()),
+            account_index: UnorderedSet::new([prefix.as_slice(), b"i"].concat()),
             accounts: LookupMap::new(prefix),
             total_supply: 0,
             account_storage_usage: 0,
             vault: env::signer_account_id(),
+            tax_policy: TaxPolicy::default(),
+            total_burned: 0,
+            total_taxed: 0,
+            total_to_vault: 0,
+            total_to_stakers: 0,
         };
         this.measure_account_storage_usage();
         this
@@ -95,8 +162,10 @@ impl FungibleToken {
         let initial_storage_usage = env::storage_usage();
         let tmp_account_id = AccountId::new_unchecked("a".repeat(64));
         self.accounts.insert(&tmp_account_id, &0u128);
+        self.account_index.insert(&tmp_account_id);
         self.account_storage_usage = env::storage_usage() - initial_storage_usage;
         self.accounts.remove(&tmp_account_id);
+        self.account_index.remove(&tmp_account_id);
     }
 
     pub fn internal_unwrap_balance_of(&self, account_id: &AccountId) -> Balance {
@@ -157,10 +226,87 @@ impl FungibleToken {
         .emit();
     }
 
+    /// Transfers `amount` less the tax of the active [`TaxPolicy`] and distributes the tax.
+    /// Returns the amount received by `receiver_id`.
+    pub fn internal_transfer_taxed(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) -> Balance {
+        let rate_bps = self
+            .tax_exemptions
+            .rate_for(&self.tax_policy, sender_id, receiver_id);
+        let tax = self.tax_policy.split_with_rate(amount, rate_bps);
+        let amount_after_tax = amount
+            .checked_sub(tax.total())
+            .unwrap_or_else(|| env::panic_str("Amount after tax overflow"));
+
+        self.internal_transfer(sender_id, receiver_id, amount_after_tax, memo);
+        self.total_taxed += tax.total();
+
+        if tax.burn > 0 {
+            self.internal_burn(sender_id, tax.burn, Some("Burn"));
+        }
+        if tax.vault > 0 {
+            let vault = self.vault.clone();
+            self.internal_transfer(sender_id, &vault, tax.vault, Some("To Vault".to_string()));
+            self.total_to_vault += tax.vault;
+        }
+        if tax.staker > 0 {
+            self.internal_transfer(
+                sender_id,
+                &env::current_account_id(),
+                tax.staker,
+                Some("To Stakers".to_string()),
+            );
+            self.total_to_stakers += tax.staker;
+        }
+        amount_after_tax
+    }
+
+    /// Withdraws `amount` from `account_id` and takes it out of the total supply.
+    pub fn internal_burn(&mut self, account_id: &AccountId, amount: Balance, memo: Option<&str>) {
+        self.internal_withdraw(account_id, amount);
+        self.total_burned += amount;
+        FtBurn {
+            owner_id: account_id,
+            amount: &U128(amount),
+            memo,
+        }
+        .emit();
+    }
+
     pub fn internal_register_account(&mut self, account_id: &AccountId) {
         if self.accounts.insert(account_id, &0).is_some() {
             env::panic_str("The account is already registered");
         }
+        self.account_index.insert(account_id);
+    }
+
+    /// Adds the registered accounts among `account_ids` to `account_index`.
+    /// Returns the number of accounts that were missing from it.
+    pub fn internal_index_accounts(&mut self, account_ids: &[AccountId]) -> u64 {
+        let mut indexed = 0;
+        for account_id in account_ids {
+            if self.accounts.contains_key(account_id) && self.account_index.insert(account_id) {
+                indexed += 1;
+            }
+        }
+        indexed
+    }
+
+    /// Sums the balances of up to `limit` registered accounts starting at `from_index`.
+    /// Returns the sum and the number of accounts read.
+    pub fn internal_sum_balances(&self, from_index: u64, limit: u64) -> (Balance, u64) {
+        let account_ids = self.account_index.as_vector();
+        let to_index = std::cmp::min(from_index.saturating_add(limit), account_ids.len());
+        let sum = (from_index..to_index)
+            .filter_map(|index| account_ids.get(index))
+            .map(|account_id| self.accounts.get(&account_id).unwrap_or(0))
+            .sum();
+        (sum, to_index.saturating_sub(from_index))
     }
 }
 
@@ -168,48 +314,8 @@ impl FungibleTokenCore for FungibleToken {
     fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
         //TAX
         assert_one_yocto();
-
-        let tax_rate = 5u128;
-        let total_tax = amount
-            .0
-            .checked_mul(tax_rate)
-            .unwrap_or_else(|| env::panic_str("Tax overflow"))
-            .checked_div(100)
-            .unwrap_or_else(|| env::panic_str("Divide overflow"));
-
-        let amount_after_tax = amount
-            .0
-            .checked_sub(total_tax)
-            .unwrap_or_else(|| env::panic_str("Amount after tax overflow"));
-
         let sender_id = env::predecessor_account_id();
-
-        self.internal_transfer(&sender_id, &receiver_id, amount_after_tax, memo);
-        //Burn the 2.5%
-        //check tax is odd or even
-        let tax_odd = total_tax % 2;
-        let burn_amount = total_tax
-            .checked_div_euclid(2)
-            .unwrap_or_else(|| env::panic_str("Burn amount overflow"));
-
-        self.total_supply -= burn_amount + tax_odd;
-
-        FtBurn {
-            owner_id: &receiver_id,
-            amount: &U128(burn_amount + tax_odd),
-            memo: Some("Burn"),
-        }
-        .emit();
-
-        if burn_amount > 0 {
-            let vault = self.vault.clone();
-            self.internal_transfer(
-                &sender_id,
-                &vault,
-                burn_amount,
-                Some("To Vault".to_string()),
-            );
-        }
+        self.internal_transfer_taxed(&sender_id, &receiver_id, amount.0, memo);
     }
 
     fn ft_transfer_call(
@@ -225,8 +331,9 @@ impl FungibleTokenCore for FungibleToken {
             "More gas is required"
         );
         let sender_id = env::predecessor_account_id();
-        let amount: Balance = amount.into();
-        self.internal_transfer(&sender_id, &receiver_id, amount, memo);
+        // The receiver is only offered, and can only refund, what is left after tax
+        let amount: Balance =
+            self.internal_transfer_taxed(&sender_id, &receiver_id, amount.into(), memo);
         // Initiating receiver's call and the callback
         ext_fungible_token_receiver::ft_on_transfer(
             sender_id.clone(),
@@ -259,6 +366,7 @@ impl FungibleTokenCore for FungibleToken {
 impl FungibleToken {
     /// Internal method that returns the amount of burned tokens in a corner case when the sender
     /// has deleted (unregistered) their account while the `ft_transfer_call` was still in flight.
+    /// `amount` is the amount received after tax, refunds are not taxed and the tax is kept.
     /// Returns (Used token amount, Burned token amount)
     pub fn internal_ft_resolve_transfer(
         &mut self,
@@ -302,6 +410,7 @@ impl FungibleToken {
                 } else {
                     // Sender's account was deleted, so we need to burn tokens.
                     self.total_supply -= refund_amount;
+                    self.total_burned += refund_amount;
                     log!("The account of the sender was deleted");
                     FtBurn {
                         owner_id: &receiver_id,
@@ -18,48 +18,6 @@ use near_sdk::json_types::U128;
 use near_sdk::AccountId;
 use serde::Serialize;
 
-#[must_use]
-#[derive(Serialize, Debug, Clone)]
-pub struct NftStake<'a> {
-    pub owner_id: &'a AccountId,
-    pub token_id: &'a String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub memo: Option<&'a str>,
-}
-
-impl NftStake<'_> {
-    /// Logs the event to the host. This is required to ensure that the event is triggered
-    /// and to consume the event.
-    pub fn emit(self) {
-        Self::emit_many(&[self])
-    }
-
-    /// Emits an FT mint event, through [`env::log_str`](near_sdk::env::log_str),
-    /// where each [`FtMint`] represents the data of each mint.
-    pub fn emit_many(data: &[NftStake<'_>]) {
-        new_141_v1(Nep141EventKind::NftStake(data)).emit()
-    }
-}
-#[must_use]
-#[derive(Serialize, Debug, Clone)]
-pub struct FtStake<'a> {
-    pub owner_id: &'a AccountId,
-    pub amount: &'a U128,
-    pub created_at: &'a u64,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub memo: Option<&'a str>,
-}
-impl FtStake<'_> {
-    pub fn emit(self) {
-        Self::emit_many(&[self])
-    }
-
-    /// Emits an FT mint event, through [`env::log_str`](near_sdk::env::log_str),
-    /// where each [`FtStake`] represents the data of each mint.
-    pub fn emit_many(data: &[FtStake<'_>]) {
-        new_141_v1(Nep141EventKind::FtStake(data)).emit()
-    }
-}
 /// Data to log for an FT mint event. To log this event, call [`.emit()`](FtMint::emit).
 #[must_use]
 #[derive(Serialize, Debug, Clone)]
@@ -149,8 +107,6 @@ enum Nep141EventKind<'a> {
     FtMint(&'a [FtMint<'a>]),
     FtTransfer(&'a [FtTransfer<'a>]),
     FtBurn(&'a [FtBurn<'a>]),
-    FtStake(&'a [FtStake<'a>]),
-    NftStake(&'a [NftStake<'a>]),
 }
 
 fn new_141<'a>(version: &'static str, event_kind: Nep141EventKind<'a>) -> NearEvent<'a> {
@@ -6,6 +6,8 @@ pub mod metadata;
 pub mod receiver;
 pub mod resolver;
 pub mod storage_impl;
+pub mod tax;
 
-pub use core_impl::FungibleToken;
+pub use core_impl::{FungibleToken, FungibleTokenV0};
+#[allow(unused_imports)]
 pub use macros::*;
@@ -16,7 +16,9 @@ impl FungibleToken {
         if let Some(balance) = self.accounts.get(&account_id) {
             if balance == 0 || force {
                 self.accounts.remove(&account_id);
+                self.account_index.remove(&account_id);
                 self.total_supply -= balance;
+                self.total_burned += balance;
                 Promise::new(account_id.clone()).transfer(self.storage_balance_bounds().min.0 + 1);
                 Some((account_id, balance))
             } else {
@@ -0,0 +1,189 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{UnorderedMap, UnorderedSet};
+use near_sdk::require;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{AccountId, Balance, IntoStorageKey};
+
+/// Denominator of every basis point value in this module.
+pub const BPS_DENOMINATOR: u16 = 10_000;
+/// Highest transfer tax rate that can be configured, 10%.
+pub const MAX_TAX_RATE_BPS: u16 = 1_000;
+
+/// Tax taken from every taxed transfer. `rate_bps` is charged on the transferred
+/// amount and the tax is split into `burn_share_bps`, `vault_share_bps` and
+/// `staker_share_bps`, which add up to [`BPS_DENOMINATOR`].
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaxPolicy {
+    pub rate_bps: u16,
+    pub burn_share_bps: u16,
+    pub vault_share_bps: u16,
+    pub staker_share_bps: u16,
+}
+
+impl Default for TaxPolicy {
+    /// 5% tax, half burned and half sent to the vault.
+    fn default() -> Self {
+        Self {
+            rate_bps: 500,
+            burn_share_bps: 5_000,
+            vault_share_bps: 5_000,
+            staker_share_bps: 0,
+        }
+    }
+}
+
+/// Parts of the tax taken from a single transfer.
+#[derive(Debug, Default, PartialEq)]
+pub struct TaxSplit {
+    pub burn: Balance,
+    pub vault: Balance,
+    pub staker: Balance,
+}
+
+impl TaxSplit {
+    pub fn total(&self) -> Balance {
+        self.burn + self.vault + self.staker
+    }
+}
+
+impl TaxPolicy {
+    pub fn assert_valid(&self) {
+        require!(
+            self.rate_bps <= MAX_TAX_RATE_BPS,
+            "Tax rate exceeds the maximum"
+        );
+        require!(
+            self.burn_share_bps as u32 + self.vault_share_bps as u32 + self.staker_share_bps as u32
+                == BPS_DENOMINATOR as u32,
+            "Tax shares must add up to 10000 bps"
+        );
+    }
+
+    /// Splits the tax on `amount`, the rounding remainder is burned.
+    pub fn split(&self, amount: Balance) -> TaxSplit {
+        self.split_with_rate(amount, self.rate_bps)
+    }
+
+    /// Splits the tax on `amount` charged at `rate_bps` instead of the policy rate.
+    pub fn split_with_rate(&self, amount: Balance, rate_bps: u16) -> TaxSplit {
+        let tax = amount * rate_bps as u128 / BPS_DENOMINATOR as u128;
+        let vault = tax * self.vault_share_bps as u128 / BPS_DENOMINATOR as u128;
+        let staker = tax * self.staker_share_bps as u128 / BPS_DENOMINATOR as u128;
+        TaxSplit {
+            burn: tax - vault - staker,
+            vault,
+            staker,
+        }
+    }
+}
+
+/// Accounts that are charged a different rate than the [`TaxPolicy`] rate.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TaxExemptions {
+    /// Transfers from these accounts are not taxed.
+    pub senders: UnorderedSet<AccountId>,
+    /// Transfers to these accounts are not taxed.
+    pub receivers: UnorderedSet<AccountId>,
+    /// Rate in basis points charged on transfers from or to these accounts.
+    pub custom_rates: UnorderedMap<AccountId, u16>,
+}
+
+impl TaxExemptions {
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let prefix = prefix.into_storage_key();
+        Self {
+            senders: UnorderedSet::new([prefix.as_slice(), b"s"].concat()),
+            receivers: UnorderedSet::new([prefix.as_slice(), b"r"].concat()),
+            custom_rates: UnorderedMap::new([prefix.as_slice(), b"c"].concat()),
+        }
+    }
+
+    /// Rate charged on a transfer from `sender_id` to `receiver_id`. Exemptions win over
+    /// custom rates and the lower custom rate of both accounts applies.
+    pub fn rate_for(
+        &self,
+        policy: &TaxPolicy,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+    ) -> u16 {
+        if self.senders.contains(sender_id) || self.receivers.contains(receiver_id) {
+            return 0;
+        }
+        [sender_id, receiver_id]
+            .iter()
+            .filter_map(|account_id| self.custom_rates.get(account_id))
+            .min()
+            .unwrap_or(policy.rate_bps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    #[test]
+    fn default_split() {
+        let split = TaxPolicy::default().split(1_001);
+        assert_eq!(split.total(), 50);
+        assert_eq!(split.vault, 25);
+        assert_eq!(split.burn, 25);
+    }
+
+    #[test]
+    fn rounding_is_burned() {
+        let policy = TaxPolicy {
+            rate_bps: 1_000,
+            burn_share_bps: 2_000,
+            vault_share_bps: 4_000,
+            staker_share_bps: 4_000,
+        };
+        policy.assert_valid();
+        let split = policy.split(99);
+        assert_eq!(split.total(), 9);
+        assert_eq!(split.vault, 3);
+        assert_eq!(split.staker, 3);
+        assert_eq!(split.burn, 3);
+    }
+
+    #[test]
+    fn exemptions_and_custom_rates() {
+        testing_env!(VMContextBuilder::new().build());
+        let policy = TaxPolicy::default();
+        let mut exemptions = TaxExemptions::new(b"x".to_vec());
+        exemptions.senders.insert(&accounts(0));
+        exemptions.receivers.insert(&accounts(1));
+        exemptions.custom_rates.insert(&accounts(2), &100);
+        exemptions.custom_rates.insert(&accounts(3), &200);
+
+        assert_eq!(exemptions.rate_for(&policy, &accounts(0), &accounts(4)), 0);
+        assert_eq!(exemptions.rate_for(&policy, &accounts(2), &accounts(1)), 0);
+        assert_eq!(
+            exemptions.rate_for(&policy, &accounts(3), &accounts(4)),
+            200
+        );
+        assert_eq!(
+            exemptions.rate_for(&policy, &accounts(3), &accounts(2)),
+            100
+        );
+        assert_eq!(
+            exemptions.rate_for(&policy, &accounts(4), &accounts(5)),
+            500
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Tax rate exceeds the maximum")]
+    fn rate_above_maximum() {
+        TaxPolicy {
+            rate_bps: MAX_TAX_RATE_BPS + 1,
+            ..TaxPolicy::default()
+        }
+        .assert_valid();
+    }
+}
@@ -2,6 +2,7 @@ use crate::non_fungible_token::token::TokenId;
 use near_sdk::AccountId;
 
 /// Approval receiver is the trait for the method called (or attempted to be called) when an NFT contract adds an approval for an account.
+#[allow(clippy::doc_overindented_list_items)]
 pub trait NonFungibleTokenApprovalReceiver {
     /// Respond to notification that contract has been granted approval for a token.
     ///
@@ -468,6 +468,7 @@ impl NonFungibleTokenCore for NonFungibleToken {
 
 impl NonFungibleTokenResolver for NonFungibleToken {
     /// Returns true if token was successfully transferred to `receiver_id`.
+    #[allow(clippy::manual_unwrap_or)]
     fn nft_resolve_transfer(
         &mut self,
         previous_owner_id: AccountId,
@@ -19,6 +19,7 @@ use near_sdk::PromiseOrValue;
 /// understand how the cross-contract call work.
 ///
 /// [core non-fungible token standard]: <https://nomicon.io/Standards/NonFungibleToken/Core.html>
+#[allow(clippy::doc_overindented_list_items)]
 pub trait NonFungibleTokenCore {
     /// Simple transfer. Transfer a given `token_id` from current owner to
     /// `receiver_id`.
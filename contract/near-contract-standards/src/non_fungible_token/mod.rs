@@ -22,6 +22,7 @@ mod utils;
 pub use utils::*;
 
 pub use self::core::NonFungibleToken;
+#[allow(unused_imports)]
 pub use macros::*;
 
 pub mod events;
@@ -16,6 +16,7 @@ use std::collections::HashMap;
 /// financial contracts, so 10 is a safe upper limit.
 
 /// This currently deviates from the standard but is in the process of updating to use this type
+#[allow(clippy::empty_line_after_doc_comments)]
 #[derive(Default, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Payout {
@@ -23,6 +24,7 @@ pub struct Payout {
 }
 
 pub mod payout_impl;
+#[allow(clippy::module_inception)]
 pub mod payouts;
 pub mod royalties;
 
@@ -0,0 +1,64 @@
+//! Ownership events of [`Upgrade`](super::Upgrade), logged in the nep-297 format:
+//! <https://github.com/near/NEPs/blob/master/specs/Standards/EventsFormat.md>
+
+use crate::event::NearEvent;
+use near_sdk::AccountId;
+use serde::Serialize;
+
+/// Data to log when the owner changes or a new owner is proposed. `new_owner_id` is
+/// `None` when ownership was renounced or a proposal was cancelled.
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct OwnerChange<'a> {
+    pub old_owner_id: Option<&'a AccountId>,
+    pub new_owner_id: Option<&'a AccountId>,
+}
+
+impl OwnerChange<'_> {
+    pub fn emit_proposed(self) {
+        new_upgrade_v1(UpgradeEventKind::OwnerProposed(&[self])).emit()
+    }
+    pub fn emit_proposal_cancelled(self) {
+        new_upgrade_v1(UpgradeEventKind::OwnerProposalCancelled(&[self])).emit()
+    }
+    pub fn emit_transferred(self) {
+        new_upgrade_v1(UpgradeEventKind::OwnerTransferred(&[self])).emit()
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct UpgradeEvent<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    event_kind: UpgradeEventKind<'a>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+enum UpgradeEventKind<'a> {
+    OwnerProposed(&'a [OwnerChange<'a>]),
+    OwnerProposalCancelled(&'a [OwnerChange<'a>]),
+    OwnerTransferred(&'a [OwnerChange<'a>]),
+}
+
+fn new_upgrade_v1(event_kind: UpgradeEventKind) -> NearEvent {
+    NearEvent::Upgrade(UpgradeEvent { version: "1.0.0", event_kind })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils;
+
+    #[test]
+    fn owner_transferred() {
+        let old_owner_id = AccountId::new_unchecked("bob".to_string());
+        OwnerChange { old_owner_id: Some(&old_owner_id), new_owner_id: None }.emit_transferred();
+        assert_eq!(
+            test_utils::get_logs()[0],
+            r#"EVENT_JSON:{"standard":"upgrade","version":"1.0.0","event":"owner_transferred","data":[{"old_owner_id":"bob","new_owner_id":null}]}"#
+        );
+    }
+}
@@ -1,20 +1,37 @@
+pub mod events;
+
+use events::OwnerChange;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::json_types::U64;
 use near_sdk::{env, require, AccountId, Duration, Promise, Timestamp};
 
 type WrappedDuration = U64;
 
+/// Storage key of the staged code.
+const UPGRADE_KEY: &[u8] = b"upgrade";
+
 pub trait Ownable {
     fn assert_owner(&self) {
-        require!(env::predecessor_account_id() == self.get_owner(), "Owner must be predecessor");
+        require!(
+            self.get_owner() == Some(env::predecessor_account_id()),
+            "Owner must be predecessor"
+        );
     }
-    fn get_owner(&self) -> AccountId;
-    fn set_owner(&mut self, owner: AccountId);
+    /// `None` once ownership was renounced.
+    fn get_owner(&self) -> Option<AccountId>;
+    fn get_pending_owner(&self) -> Option<AccountId>;
+    /// Proposes `owner` as the next owner, who takes over by calling `accept_owner`.
+    fn propose_owner(&mut self, owner: AccountId);
+    fn accept_owner(&mut self);
+    fn cancel_owner_proposal(&mut self);
+    /// Leaves the owner unset for good, along with any pending proposal.
+    fn renounce_ownership(&mut self);
 }
 
 pub trait Upgradable {
     fn get_staging_duration(&self) -> WrappedDuration;
     fn stage_code(&mut self, code: Vec<u8>, timestamp: Timestamp);
+    fn cancel_code(&mut self);
     fn deploy_code(&mut self) -> Promise;
 
     /// Implement migration for the next version.
@@ -27,25 +44,78 @@ pub trait Upgradable {
 
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct Upgrade {
-    pub owner: AccountId,
+    pub owner: Option<AccountId>,
+    pub pending_owner: Option<AccountId>,
     pub staging_duration: Duration,
     pub staging_timestamp: Timestamp,
 }
 
 impl Upgrade {
     pub fn new(owner: AccountId, staging_duration: Duration) -> Self {
-        Self { owner, staging_duration, staging_timestamp: 0 }
+        Self { owner: Some(owner), pending_owner: None, staging_duration, staging_timestamp: 0 }
+    }
+
+    /// Stages `code` to be deployed from `timestamp` on, without checking the caller.
+    pub fn internal_stage_code(&mut self, code: Vec<u8>, timestamp: Timestamp) {
+        require!(
+            env::block_timestamp() + self.staging_duration < timestamp,
+            "Timestamp must be later than staging duration"
+        );
+        // Writes directly into storage to avoid serialization penalty by using default struct.
+        env::storage_write(UPGRADE_KEY, &code);
+        self.staging_timestamp = timestamp;
+    }
+
+    /// Drops the staged code, without checking the caller.
+    pub fn internal_cancel_code(&mut self) {
+        require!(env::storage_remove(UPGRADE_KEY), "No upgrade code available");
+        self.staging_timestamp = 0;
+    }
+
+    /// Sha256 of the staged code, `None` when nothing is staged.
+    pub fn staged_code_hash(&self) -> Option<Vec<u8>> {
+        env::storage_read(UPGRADE_KEY).map(|code| env::sha256(&code))
     }
 }
 
 impl Ownable for Upgrade {
-    fn get_owner(&self) -> AccountId {
+    fn get_owner(&self) -> Option<AccountId> {
         self.owner.clone()
     }
 
-    fn set_owner(&mut self, owner: AccountId) {
+    fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner.clone()
+    }
+
+    fn propose_owner(&mut self, owner: AccountId) {
+        self.assert_owner();
+        OwnerChange { old_owner_id: self.owner.as_ref(), new_owner_id: Some(&owner) }
+            .emit_proposed();
+        self.pending_owner = Some(owner);
+    }
+
+    fn accept_owner(&mut self) {
+        let owner = env::predecessor_account_id();
+        require!(self.pending_owner.as_ref() == Some(&owner), "Pending owner must be predecessor");
+        OwnerChange { old_owner_id: self.owner.as_ref(), new_owner_id: Some(&owner) }
+            .emit_transferred();
+        self.owner = Some(owner);
+        self.pending_owner = None;
+    }
+
+    fn cancel_owner_proposal(&mut self) {
         self.assert_owner();
-        self.owner = owner;
+        let pending_owner =
+            self.pending_owner.take().unwrap_or_else(|| env::panic_str("No pending owner"));
+        OwnerChange { old_owner_id: Some(&pending_owner), new_owner_id: None }
+            .emit_proposal_cancelled();
+    }
+
+    fn renounce_ownership(&mut self) {
+        self.assert_owner();
+        OwnerChange { old_owner_id: self.owner.as_ref(), new_owner_id: None }.emit_transferred();
+        self.owner = None;
+        self.pending_owner = None;
     }
 }
 
@@ -56,28 +126,99 @@ impl Upgradable for Upgrade {
 
     fn stage_code(&mut self, code: Vec<u8>, timestamp: Timestamp) {
         self.assert_owner();
-        require!(
-            env::block_timestamp() + self.staging_duration < timestamp,
-            "Timestamp must be later than staging duration"
-        );
-        // Writes directly into storage to avoid serialization penalty by using default struct.
-        env::storage_write(b"upgrade", &code);
-        self.staging_timestamp = timestamp;
+        self.internal_stage_code(code, timestamp);
+    }
+
+    fn cancel_code(&mut self) {
+        self.assert_owner();
+        self.internal_cancel_code();
     }
 
     fn deploy_code(&mut self) -> Promise {
-        if self.staging_timestamp < env::block_timestamp() {
-            env::panic_str(
-                format!(
-                    "Deploy code too early: staging ends on {}",
-                    self.staging_timestamp + self.staging_duration
-                )
-                .as_str(),
-            );
-        }
-        let code = env::storage_read(b"upgrade")
-            .unwrap_or_else(|| env::panic_str("No upgrade code available"));
-        env::storage_remove(b"upgrade");
+        require!(
+            env::block_timestamp() >= self.staging_timestamp,
+            format!("Deploy code too early: staging ends on {}", self.staging_timestamp)
+        );
+        let code = env::storage_read(UPGRADE_KEY).unwrap_or_default();
+        require!(!code.is_empty(), "No upgrade code available");
+        env::storage_remove(UPGRADE_KEY);
+        self.staging_timestamp = 0;
         Promise::new(env::current_account_id()).deploy_contract(code)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    #[test]
+    fn two_step_ownership() {
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        let mut upgrade = Upgrade::new(accounts(0), 0);
+        upgrade.propose_owner(accounts(1));
+        assert_eq!(upgrade.get_owner(), Some(accounts(0)));
+        assert_eq!(upgrade.get_pending_owner(), Some(accounts(1)));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        upgrade.accept_owner();
+        assert_eq!(upgrade.get_owner(), Some(accounts(1)));
+        assert_eq!(upgrade.get_pending_owner(), None);
+
+        upgrade.renounce_ownership();
+        assert_eq!(upgrade.get_owner(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Pending owner must be predecessor")]
+    fn accept_without_proposal() {
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        let mut upgrade = Upgrade::new(accounts(0), 0);
+        upgrade.propose_owner(accounts(1));
+        upgrade.cancel_owner_proposal();
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        upgrade.accept_owner();
+    }
+
+    #[test]
+    fn deploy_after_staging() {
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        let mut upgrade = Upgrade::new(accounts(0), 10);
+        upgrade.stage_code(vec![1, 2, 3], 20);
+        assert_eq!(upgrade.staged_code_hash(), Some(env::sha256(&[1, 2, 3])));
+
+        testing_env!(context.block_timestamp(20).build());
+        upgrade.deploy_code();
+        assert_eq!(upgrade.staged_code_hash(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Deploy code too early: staging ends on 20")]
+    fn deploy_too_early() {
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        let mut upgrade = Upgrade::new(accounts(0), 10);
+        upgrade.stage_code(vec![1, 2, 3], 20);
+
+        testing_env!(context.block_timestamp(19).build());
+        upgrade.deploy_code();
+    }
+
+    #[test]
+    #[should_panic(expected = "No upgrade code available")]
+    fn deploy_cancelled_code() {
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        let mut upgrade = Upgrade::new(accounts(0), 10);
+        upgrade.stage_code(vec![1, 2, 3], 20);
+        upgrade.cancel_code();
+
+        testing_env!(context.block_timestamp(20).build());
+        upgrade.deploy_code();
+    }
+}
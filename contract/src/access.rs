@@ -0,0 +1,134 @@
+use super::*;
+use crate::events::{MinterAllowanceSet, RoleChange};
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, Clone, Copy, PartialEq,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Grants and revokes roles and sets minter allowances.
+    Admin,
+    /// Mints NEKO within its allowance.
+    Minter,
+    Pauser,
+    /// Sets the bake fee and the early exit penalty and withdraws pool fees.
+    FeeManager,
+}
+
+/// Roles the owner is given on init, they follow ownership.
+pub const OWNER_ROLES: [Role; 3] = [Role::Admin, Role::FeeManager, Role::Pauser];
+
+//Call Methods
+#[near_bindgen]
+impl Contract {
+    pub fn grant_role(&mut self, account_id: AccountId, role: Role) {
+        self.assert_role(Role::Admin);
+        self.assert_no_multisig();
+        self.internal_admin_grant_role(account_id, role);
+    }
+    pub fn revoke_role(&mut self, account_id: AccountId, role: Role) {
+        self.assert_role(Role::Admin);
+        self.assert_no_multisig();
+        self.internal_admin_revoke_role(account_id, role);
+    }
+    /// Sets how much NEKO `account_id` may still mint, the account needs the minter role.
+    pub fn set_minter_allowance(&mut self, account_id: AccountId, allowance: U128) {
+        self.assert_role(Role::Admin);
+        self.assert_no_multisig();
+        self.internal_set_minter_allowance(account_id, allowance);
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_admin_grant_role(&mut self, account_id: AccountId, role: Role) {
+        if self.internal_has_role(&account_id, role) {
+            return;
+        }
+        self.internal_grant_role(&account_id, role);
+        RoleChange {
+            account_id: &account_id,
+            role: &role,
+            by: &env::predecessor_account_id(),
+        }
+        .emit_granted();
+    }
+    pub(crate) fn internal_admin_revoke_role(&mut self, account_id: AccountId, role: Role) {
+        require!(
+            self.internal_has_role(&account_id, role),
+            "Account does not have this role"
+        );
+        if role == Role::Admin {
+            let admins = self
+                .roles
+                .values()
+                .filter(|roles| roles.contains(&Role::Admin))
+                .count();
+            require!(admins > 1, "Cannot revoke the last admin");
+        }
+        self.internal_revoke_role(&account_id, role);
+        RoleChange {
+            account_id: &account_id,
+            role: &role,
+            by: &env::predecessor_account_id(),
+        }
+        .emit_revoked();
+    }
+    pub(crate) fn internal_set_minter_allowance(&mut self, account_id: AccountId, allowance: U128) {
+        require!(
+            self.internal_has_role(&account_id, Role::Minter),
+            "Account is not a minter"
+        );
+        self.minter_allowances.insert(&account_id, &allowance.0);
+        MinterAllowanceSet {
+            account_id: &account_id,
+            allowance: &allowance,
+        }
+        .emit();
+    }
+    pub(crate) fn internal_has_role(&self, account_id: &AccountId, role: Role) -> bool {
+        self.roles
+            .get(account_id)
+            .map(|roles| roles.contains(&role))
+            .unwrap_or(false)
+    }
+    /// Role check on the direct caller.
+    pub(crate) fn assert_role(&self, role: Role) {
+        if !self.internal_has_role(&env::predecessor_account_id(), role) {
+            panic!("Missing role {:?}", role);
+        }
+    }
+    pub(crate) fn internal_grant_role(&mut self, account_id: &AccountId, role: Role) {
+        let mut roles = self.roles.get(account_id).unwrap_or_default();
+        if !roles.contains(&role) {
+            roles.push(role);
+            self.roles.insert(account_id, &roles);
+        }
+    }
+    /// Returns whether `account_id` had `role`.
+    pub(crate) fn internal_revoke_role(&mut self, account_id: &AccountId, role: Role) -> bool {
+        let mut roles = match self.roles.get(account_id) {
+            Some(roles) if roles.contains(&role) => roles,
+            _ => return false,
+        };
+        roles.retain(|r| *r != role);
+        if roles.is_empty() {
+            self.roles.remove(account_id);
+        } else {
+            self.roles.insert(account_id, &roles);
+        }
+        if role == Role::Minter {
+            self.minter_allowances.remove(account_id);
+        }
+        true
+    }
+    /// Spends `amount` of the allowance of the calling minter.
+    pub(crate) fn internal_use_minter_allowance(&mut self, amount: Balance) {
+        self.assert_role(Role::Minter);
+        let minter_id = env::predecessor_account_id();
+        let allowance = self.minter_allowances.get(&minter_id).unwrap_or(0);
+        require!(amount <= allowance, "Mint exceeds minter allowance");
+        self.minter_allowances
+            .insert(&minter_id, &(allowance - amount));
+    }
+}
@@ -11,7 +11,7 @@ pub trait Factory {
 }
 #[ext_contract(ext_self)]
 pub trait NekoStakeCallBack {
-    fn setup_account_call_back(&mut self);
+    fn setup_account_call_back(&mut self, account_id: AccountId);
 }
 #[near_bindgen]
 impl Contract {
@@ -20,22 +20,23 @@ impl Contract {
             env::attached_deposit() > STORAGE_COST * 2,
             "Insufficient deposit"
         );
+        let account_id = env::predecessor_account_id();
         ext_factory_contract::storage_deposit(
-            Some(env::signer_account_id()),
+            Some(account_id.clone()),
             Some(true),
-            self.factory_id.clone(),
+            self.config.factory_id.clone(),
             STORAGE_COST,
             env::prepaid_gas(),
         )
         .then(ext_self::setup_account_call_back(
+            account_id,
             env::current_account_id(),
             STORAGE_COST,
             env::prepaid_gas(),
         ))
     }
     #[private]
-    pub fn setup_account_call_back(&mut self) {
-        self.token
-            .storage_deposit(Some(env::signer_account_id()), Some(true));
+    pub fn setup_account_call_back(&mut self, account_id: AccountId) {
+        self.token.storage_deposit(Some(account_id), Some(true));
     }
 }
@@ -0,0 +1,61 @@
+use super::*;
+use crate::stake_events::Compound;
+
+//Call Methods
+#[near_bindgen]
+impl Contract {
+    pub fn set_auto_compound(&mut self, enabled: bool) {
+        let account_id = env::predecessor_account_id();
+        self.internal_upgrade_stake(&account_id);
+        let mut stake_data = self
+            .stake
+            .get(&account_id)
+            .unwrap_or_else(|| panic!("No stake record found for this account"));
+        stake_data.auto_compound = enabled;
+        self.stake.insert(&account_id, &stake_data);
+    }
+    /// Keeper entry point, compounds the accrued reward of an account that
+    /// opted in to auto-compounding. Returns the amount added to its stake.
+    pub fn compound(&mut self, account_id: AccountId) -> U128 {
+        self.assert_not_paused(Feature::Claiming);
+        require!(!self.emergency_mode, "Claiming is closed in emergency mode");
+        U128(
+            self.internal_compound(&account_id)
+                .unwrap_or_else(|| panic!("Nothing to compound for this account")),
+        )
+    }
+}
+
+impl Contract {
+    /// Converts the accrued reward of `account_id` into stake when it opted in, as far
+    /// as the reserve surplus can back it. The reward cookies were never minted, so the
+    /// stake comes out of the surplus without going through the factory and what the
+    /// surplus can't cover stays in `acc_reward`. Returns the amount compounded.
+    pub(crate) fn internal_compound(&mut self, account_id: &AccountId) -> Option<Balance> {
+        if self.emergency_mode || self.paused.is_paused(Feature::Claiming) {
+            return None;
+        }
+        self.internal_upgrade_stake(account_id);
+        let mut stake_data = self.stake.get(account_id)?;
+        if !stake_data.auto_compound {
+            return None;
+        }
+        self.checkpoint_reward();
+        stake_data.update_reward(&self.reward_index);
+        let amount = std::cmp::min(stake_data.acc_reward, self.reserve_surplus());
+        if amount == 0 {
+            return None;
+        }
+        stake_data.acc_reward -= amount;
+        stake_data.deposit(amount, &mut self.reward_index);
+        self.stake.insert(account_id, &stake_data);
+        self.total_staked += amount;
+        Compound {
+            account_id,
+            reward: &U128(amount),
+            amount: &U128(amount),
+        }
+        .emit();
+        Some(amount)
+    }
+}
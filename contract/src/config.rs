@@ -0,0 +1,107 @@
+use super::*;
+use crate::lockup::{LockTier, MULTIPLIER_BASE};
+use serde::Serialize;
+
+/// Denominator of the bake fee.
+pub const BPS_DENOMINATOR: u16 = 10_000;
+pub const DEFAULT_MAX_BAKE_FEE_BPS: u16 = 2_000;
+pub const DEFAULT_MAX_REWARD_RATE: u8 = 10;
+
+/// Contract configuration, every change to it is validated against its bounds.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Debug, Clone)]
+pub struct Config {
+    pub factory_id: AccountId,
+    pub vault_id: AccountId,
+    /// Fee taken from stakes and cookie claims.
+    pub bake_fee_bps: u16,
+    pub max_bake_fee_bps: u16,
+    /// Highest emission rate, in percent of stake per minute.
+    pub max_reward_rate: u8,
+    /// Nanoseconds unstaked NEKO stays locked before withdrawal, 0 disables unbonding.
+    pub unbonding_period: u64,
+    pub lock_tiers: Vec<LockTier>,
+    /// Penalty for unstaking a position before unlock, early exit is disabled when `None`.
+    pub early_exit_penalty_bps: Option<u16>,
+}
+impl Config {
+    pub fn new(factory_id: AccountId, vault_id: AccountId, bake_fee_bps: u16) -> Self {
+        let config = Self {
+            factory_id,
+            vault_id,
+            bake_fee_bps,
+            max_bake_fee_bps: DEFAULT_MAX_BAKE_FEE_BPS,
+            max_reward_rate: DEFAULT_MAX_REWARD_RATE,
+            unbonding_period: 0,
+            lock_tiers: vec![],
+            early_exit_penalty_bps: None,
+        };
+        config.assert_bake_fee(bake_fee_bps);
+        config
+    }
+    pub fn assert_bake_fee(&self, bake_fee_bps: u16) {
+        if bake_fee_bps > self.max_bake_fee_bps {
+            panic!(
+                "Bake fee of {} bps exceeds the maximum of {} bps",
+                bake_fee_bps, self.max_bake_fee_bps
+            );
+        }
+    }
+    pub fn assert_reward_rate(&self, reward_rate: u8) {
+        if reward_rate > self.max_reward_rate {
+            panic!(
+                "Reward rate of {}% per minute exceeds the maximum of {}%",
+                reward_rate, self.max_reward_rate
+            );
+        }
+    }
+    pub fn assert_lock_tiers(tiers: &[LockTier]) {
+        for tier in tiers.iter() {
+            require!(
+                tier.multiplier_bps as u128 >= MULTIPLIER_BASE,
+                "Lock multiplier must be at least 1x"
+            );
+        }
+    }
+    pub fn assert_early_exit_penalty(penalty_bps: Option<u16>) {
+        if let Some(penalty_bps) = penalty_bps {
+            require!(
+                penalty_bps as u128 <= MULTIPLIER_BASE,
+                "Penalty must not exceed 100%"
+            );
+        }
+    }
+    /// Bake fee charged on `amount`, rounded down.
+    pub fn bake_fee(&self, amount: Balance) -> Balance {
+        amount * self.bake_fee_bps as u128 / BPS_DENOMINATOR as u128
+    }
+}
+
+impl Contract {
+    /// The bounds can't go below the bake fee and the reward rates in force.
+    pub(crate) fn assert_config_bounds(&self, max_bake_fee_bps: u16, max_reward_rate: u8) {
+        require!(
+            max_bake_fee_bps <= BPS_DENOMINATOR,
+            "Maximum bake fee must not exceed 10000 bps"
+        );
+        require!(
+            max_bake_fee_bps >= self.config.bake_fee_bps,
+            "Maximum bake fee is below the current bake fee"
+        );
+        require!(
+            self.emission
+                .epochs
+                .iter()
+                .all(|epoch| epoch.reward_rate <= max_reward_rate),
+            "Maximum reward rate is below a scheduled reward rate"
+        );
+    }
+    pub(crate) fn internal_set_config_bounds(
+        &mut self,
+        max_bake_fee_bps: u16,
+        max_reward_rate: u8,
+    ) {
+        self.assert_config_bounds(max_bake_fee_bps, max_reward_rate);
+        self.config.max_bake_fee_bps = max_bake_fee_bps;
+        self.config.max_reward_rate = max_reward_rate;
+    }
+}
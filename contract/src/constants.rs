@@ -2,4 +2,16 @@ use super::*;
 
 pub const MAX_SUPPLY: Balance = 10_000_000_000;
 
+/// Most keys `clear_storage` removes in one call.
+pub const MAX_MAINTENANCE_BATCH: usize = 100;
+
+/// Minimum nanoseconds between staging code and deploying it, 1 day.
+pub const STAGING_DURATION: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// Gas for the `migrate` call that follows a deployment.
+pub const GAS_FOR_MIGRATE: Gas = Gas(100_000_000_000_000);
+
+/// Nanoseconds a multisig proposal can be confirmed for, 7 days.
+pub const MULTISIG_PROPOSAL_LIFETIME: u64 = 7 * 24 * 60 * 60 * 1_000_000_000;
+
 pub const DATA_IMAGE_SVG_NEAR_ICON: &str = "data:image/svg+xml,%3Csvg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink' version='1.1' id='Layer_1' x='0px' y='0px' width='50px' height='50px' viewBox='0 0 50 50' enable-background='new 0 0 50 50' xml:space='preserve'%3E%3Cimage id='image0' width='50' height='50' x='0' y='0' href='data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAADIAAAAyCAYAAAAeP4ixAAAABGdBTUEAALGPC/xhBQAAACBjSFJN AAB6JgAAgIQAAPoAAACA6AAAdTAAAOpgAAA6mAAAF3CculE8AAAABmJLR0QA/wD/AP+gvaeTAAAA CXBIWXMAAA7EAAAOxAGVKw4bAAAAB3RJTUUH5gMfBR8eco2eMAAAGINJREFUaN6tmnmQZVd93z9n udvbX6/TPfuMZkYjCSQkDRgMFqtZnMIGgsJWdgUMMQhChbhSOIljgymWGFJxjE3s4BjiYMCJDYSY LWAgYrFAQuuMNPtMT7d6e/26+213O0v+eK3NC0uSU3Xr3Vd169zf9/x+5/f7fn/nCn7C0bnns1z+ 9h8RJC2yzmVG66cJdj292l27fCzLhzc7Y25U3h4Tnt0CWkKIKIi0D6s6Hbhiq7BmyTrxIELeKXT0 g6g9d65/11+nydRB6nuuoRhs8NSX/wvmn/Lin8gu8eM+ePHeb7H0pX+LCOro7AK1G18l+/d85rgz wxd5n7/QFKMnKfKpOHA60RqlQoRMEDLAC42TAuMlhXGMCsMoz4q0zNbyrLzXZuKLQiZffv6HT53/ 2luO+Oa+6ykHXZ77q39GrT39/w/IZ951Kz/1bz7Nt2+7nmMveIccPfTHT1Uie30Qu5+rNPx8rDJk bxMGQ7wBIWOcrOJUFacqeBnhkSAUQiqc0HigtCWjNGV7u8d2r385K4rPeqn++DV/cOneT992Lee+ cooTb34tL/znn/h/A7Jw5gG+/IHXocKE/Mo9NOeOHpqou7fP7Qlf05pkqlIXBJGAYZ/84XXy7pDS BuRUyWxA4SOcSpBhiAwlWipCHaGDBCUVQoxf753DBlXK5kGuPHjHw1vLlz6Grv5+2VtemrjmRlyR 8fPv/vb/HZA7PvdR7vj4u9BJjWTP9bq4/P1XTE2JXz96LLl2ppXjswEC0K0mhQ1YfnCFlaWUYaFw KIQEKQSBFuhI4COBEwIvFEKFxGGVelKnHteIdMTkDS+hfd0L6C2f4fRffYLLJ+/8fl7mv/Ha/3Tp i1/6racDhhOv+h2mjv70jw/krz7+W9z3qQ8R1CdxQavps5V37tsfve26a6JqzWxiBkPQmlTW6PRC NtdLTJqTBJ5qDHEo0FogHze7d+A8GOcpnCd1MPJgZESztZtrXvh2pg/ciB2uka2dZuXsXZy5+6+7 2/3u++sH5n93dzKVteIaE7f8Cq1jt/xoIPd84ePc/h/fjk4mEEFt1uXdDx0+lrzm2iNCyO4arijJ VYXlfsz2lifxlkbFU6lIglggtUAqgZByZ3YBeLwHZz2u9JjMYguHNZ7MebYtFJUp5q59PgcO3Ugo SlzRZ7jVYenM/Sbx5vcb0wd/3aRbPe8srWf+CpPXPufvB7Jy+m6++J5/iPUKryuzLtv4yNXXVl52 bJ/BddZxCDpFwtK6JLSO6TpU6oogkkgtEFKAkgglEUohpEDI8VvG9wIEeOspR4Ziu6AYGkzuyEpH 13pcaw+Hr30Ws3OHUUGMimr0Fx70w6VzH3VB5VcxWc95y9E3ffoJQNTj/1xv70IIRZA0mna0/h+O XRPfemxPgetsYKXmyiBhZQ2mY8+uaUWlpQliiQwVMtDIKEDFIUElIqgnBPUYXY1QkUSFChXp8W+s CWshYT1ERwIhPdJBxQPpNlcePocRgqn548QT+0h2XSVcProx71xObFj/hvDG3vbyJ/Phz93/qO3y kZvP/uatqCChvv9GnfVW33ngcPjqo7sL3MYGVmgubEZsbzj2NQXTM5qwJpFaIsMAFUfoWkzYrhJN 1ggnqgT1CBUHSLXjdO/xzuG9H28WQEWKaKpCdVeF2kxE3NA0A8kul7P8wNc5dc9XMc6h4zatY08R lan2bQzW33z4jZ/E6YiHPvvOJ4bW+Tu/zqGbns2/f0lMpb33H83OuD+66RpXldtdLJKLWzH5EObb kmo7QOpx3MtQo5MIXYtQSYjUj64L3jlcafCmxDv3uGAWj71YCgQCEDjj8QaKfslgZcBwM2XFSWZv +gWe/MxbEcUCeeduOicvdYYD/2pZjL5qVxa47oNnH5vvv9z20xT9Dg59OAkGnzvxFH1tI+/gnGdx kNDbFsy3JM35KvFUDSElzhi8d+hqiAzUzkbY2QfeY7IMb8wPzf0egXOauFElSyEbWHQoqDTbbJ8b 0j1/io6M2HvieRw6Mo/PV0nXMzoXet9JjXi5kqwSt7nuTR9HPvC/PklQaTD7jF+SlIN/emC/vrbp e3jj2DIx/aFiti6oTCVEcxMsrVrOX8jYGoXoZhMdh7g8x6YpNs9wRYEtivE6K/XDUDDsCWRUod/J 6F7s0l/ZZunBLp2VCpMnXk3SnGTKF6zd9y06lx8Eo4nr09Qn28/QtnjD5NL38dUJRlurqKc3lhmt Xqa/cP/T2m37vmsOmIoYDihVyPIgpikdUU2hplr43LB5sUM5LBj2LIOuQwUBSTPCmQJvLTiHDAJU GCK03tke/ompUsBmx+BkTK3iGK33aMw3mD7QRKuYjcV1mgdPoFyfonMJaR3D1NKaPkAQTRJOHCLv 9Q5t+eRLctTd6C/chYzqE+x99j+Wkuz1+/aqqbAY4KVgy8aE1lOrSQoVEtUjzGaPuBJy6IYZrn36 DM2ZiCunt1lbLAiSyg7lEAgh8TuhJnWA1PoJiT5LHQ8vFtTbAaPOAKIq1akqQesIMze8irgS0l8+ S3VuN2FNE0qH3dxgfbWHmrmZ5PDzaVz1tIM60K+tbFxE1maR/aVzLHzzk8frdfEPdjVLvDEUKmKU SmraI2ONiBJC6cgKQW3PFK09LeJGzPyROvNH6jx8vsfmekkQR4DHlQXeGry1OGtwxsDjnHLlQkpU jQiFZTiAxq4aKmqjG88knLyJxvxx0o2zCNElboQEoSQwOesXzpC5CkI44npOmMhXpLPH5+3qSdRz pzfRcfUX9+3hFXONETjo5DFu5Gg0FU4FRBMNosCxvS2Y2t8gShTej+O8WlMoBUsXBtTaCXEjwj6S raz9Wxt+Y61gZdlw5EkTpBspulaluauBbt6Cql2PUCHFsENv8U6akxkmTXGlweWWYjjEC0Nsf4Bd u50izSfyzN9t8+37dfvos6q+f/GFs5MeiaPQIf2hpKUNQUUzzCSViibNC/rGsC+Wj4Lw1oI1zO5J 8B6unB1y6PoJKu0YWxTgHEj5aKL3DqIs54ZnThNIQxnGTOypoqrXoes3gQjBe4IoochyPAlBPaHo 5UhtCMqSh+/+AvWyShgIlEflZfKi8sDPflqbQedouymf1GpYJAGDIsTnhqilkFpivSSIJGubJYW3 aC3HYWItWIv3HqEkc4eb6HjEmbs7TM1VkUrgrAcBUo45mA4llUZCXNFIFZK0QMb70e3ngKrt1J8R vrjMcJBiTRNdjQiqIXpQEpSWvJexuVIyPROgvKM05sTo/P/eraPA3jQ5qaeiRCDQ9DYcEQ6dhHgg zR0I6A8N1voxd3IW7yx4N+ZUWoMQTM4l9Fd6bF/pAm7MeK0fY3ZgHCAVOgqotUKm9k4xfe3PIKNd O0XTYgf34tIHyLMCYxxRIAlqMUEvx2SOSDm6GwWTTYPyIBF78rx/nW7U1Y2TM0qrxGKcYjTq0dSg I4XzsDXM2OM9ZekRCITz49h3DiF3QEgBDorUMLtHgYvx3uOtxxYWk5bYbMx2jXVkpWGwnnF6KWXp 0l9w4ETK9OEbIL+I2bodYTOscTtABCoO0EmAGhriwLM9gjIvCQJBIMuqs+X1ut5Ux2sNAEdhwWZ2 TAQDifOetDCUpUFJgREe78wYhHoMhC08ncUhwmXUGowZ8COsNNEEjRCXW8yoxAxLotLRqAiyoqC7 fi/3f/4cu65+KvuPekLdxeQGb8e8zHsPQqAijVRjoVYMPVlm0UqihRFFnh3XSVXuDkSGz3KyIsQb hw4UUisE44nyzBLFikG/wBuH0I+Fk7OejaUBwqRUm393ERdCoCtjVmzqhmIzpeiXRNIx24becMDK /V8n7dQ5dmIanN9hYOALizd27HUhkFJgDKSpoxILhC9Js2yvlsK3RJHhZUFRKKQHFYw1hdJjpdfb yqjVA65cHlBaQRwH+J0lN6MhSZyiqo8zXCl0FCGUeoyyCAVIhBbomscaTzkoAc/kTML00TpOB5jC UZ2ps/9JljhWlJtDnLHY3GDNYxIqyy3OgPCCoigmpPc2xI2zj7MOKcZZSGqJTgLqNc366pBaRZNm gtX1ciyiBJgsw2Qpj6dUHk3UaBJUa4xSydqaJ8sEJjNY48ALEBIVaEAgK3Umju9l+vAEcS3g8ukt OqspB45PEmhBvjki30rJtwrKFB5hO8Z4rLF463DGhtp575FjRYffcakAoSUqDpiejHjg7JCs8Mzu qnHHdzcoCs/MtCTwj5sZKDKQcYIKAkxpWDq9xsOXRuzbGzA9Hex0U/SYyngwXlGdbhFUFM5Y1h5a 48qZHhdPBzzz5w6gi5x0M8N7KFKBKcep3I/lDc46xsnTo/PS5BaBUBolBH78GEIKZBTQnkpor+Sc eXCb40+ewfkeixe7tOIYXXmi5B/2Be2Gpiwso62MXRPQ1JqoPonN+tg8Q0UBQo9dOCwVzYoiz0qy fkmrKjgwp7jSk2x3M2omoxxZnJeYUuDcY/pfyXFRttaDV5nuZflmYZkVUhEEAr/T+hhrbEnSrHB4 f8mZsyNO3dVh154Ks1OSJHk8JfdY69nuOpIJw/riNg9fGnLdzS2md+9BtZ/B+nf+BJtvIVWJChVe CLZ6hla/YPXSFgvnB9x8yy4O7J+gPfRsbaTUI49zAmMEzu10YgDnHcGYWFNagfNyQ45Ks5gb8EIQ hgKkxDn/KFuVUUB7d4Orj8TMVg0bC3266+WjALz1uNLhUkM2zLn8wDq6zNl7pEF9fj/J/hczWDxH vt2hHBqyXsmwk5Fv5fjScvrOVczGgLm9FZJGTNSssLbQo7M8REUS5wTOCvy4/mKtx+OItAfvyUqB 9XJBWydODjP3fBCEWiBDiTHuMbYqBCqJaO5uECUDkrWcyVmJdx5vHK4ct3Z8aZltO7LU0R+ETE4m bG42GZ35Bmbh20STR6nuvhrbP4UrOggBV80rVpZSFh7O2T8VsrE8xG/16S9sUSQJQgm8l3gH3o1X trAeJR2hGps4ynHWyVPaC/WDfmoL50WoJcQVTVYUeOt29PV4AhmEWBVwebFPremRlRibW1xhcKXD ypD2gTppZ8jGasba6VWkXyZ2I4xuMvfU11GtbVGsn8ObJjiH8J7GVEA53GD11BrD0NOqQHsmYctp vPNjb7jHpPGo8FRii1JgnaCXqYHx6h4tVPSDQTpYKwx7Ei2o1zXdhzNcaXdS5SMxppBCYC30u0Ni CbZQeOMoctgYaeRESPtwg+r8BL2FdbLV8xQGlrOQ3eUipnsnvhyCc/gdviacYariqDuHDiStAw22 rUZ0Da70PNK38H6crYa5Yc+EQwBpIRhk6rIMqqdk0t53flRwz/bQAZJ2NaBAkg9LvHu8RBXoIKDV 1AxThSQHK/BWIrwn8AWLD25x5p4+W/4qJp/1dqae/npkMoHLB6zc9yVM2kU4C7bElwUmzdm6NKAc GYJI0j7cxCC5eP82zo15mrf+URBFCU6UNCvj3Lo1kuQmuONJb/n8snrxkWVDWG0Hwv/cdDNG4xlk ApOX1CcTRKAe093W4QtHYRs0aznCeWyhwDq0K9HOsN0Zcvbe+1i5dI547jiBHSH7i2xulfS2Crqd nIXLKQuXM4arGW67oDCgJ2Js6ehfHrDZh5kDCUFqGHUtXgi8E2ymhnpjxExz7Knz67rsFdX3r97+ ew/qpL0P68VXOv3BpVHuD9SUZlc75vJih9HagGRajNs9ApCSRiPEyDmGrko9vEg+FNgCbOGQ1jIZ QdKwdDbOcPqbW+zWXSJh8S5jtAx5aXFCIIxDVMFr2E4h3TDUhcXk4JSkVZeMzpmxN5ygtJC5jINt iwB6maA7DB6QUfP2oFJHNmev49XvPXUuHarPLK+VIBMalSq1SpPu0ohiK8UMC3xpYYeHJXLAqNyF ISFMcpxxOMOjVbY9f5ATv/B6rrvxGgJhUKEiloZ2aJhqaa4/0WT/LkUgx4W3NRGgawFTR5q4QNGY 1EQe0p7FeYFzkq2sZKKVU4vHjcrFrmJUhJ/snjm5/qQXvRV16y0z3P3f3wfEy/kof+l0u9aIwpik 2mS9MyQUJUrL8coYjyscPh2QZtAflrQnSoLGfvJeii0LWtc8j6mnvYb1iyfZPvl10tLTONAm1B4v BMF0DacVlUaIDiSVyRhRDTl1akhU11QnIyZnIrLVkmHXgZekpWMkBhzeXaIlbI8EZ1aik0a3f602 0eoVeR/1od//FL2lC7zsPd9YfekNEy2cu2V6skVUaaMmjrC+vEJMBtZjM4vNHZQWm26zuFpg1BT7 n/1moskjrC5dIg9a9C58j+zct9AYUhdSiggqGh9rBps5p08N6aceGyhM4THrOWXmWVzOmZ6PqUea 9TM5zgish2455OB8RqPiMR4eWgrsRlZ9d3/h8td+6rbf4yVvfC/qQx/+KK/86TlecX0dKZOHhr3B M2v1xp72wRtpP+VWctGge+YuAlvicje+SodwBrzlynLGKJPsuenF1Pdex+DsN/HLJ1E7VCdUnjwt 6SwPKPsFkw1Jq6kRSpBuZNhugTBQCRV7r6ozNZuwcn9KPvLgYSPPmJ7OmWuPCe2VdcnZlehLqjr/ m5XJdp51r/AXt58dHyt8/C9/wO0feAe6IgZeVZaGqX3J3ImXJZVdR2nOXUVvWNC7eB+Bs+NKbhze eZLAo4Th7KkHWbtyiV0H9jMz1UH6Lcp8LAuEc4RYYgndXBDP1EhaCc1GRJgZhIGoFjJzrEFjNmb1 5IhhZ9xC6uY59QnBwV0BQhi6fbjnol4Y2fqbzWjjoisG/JM/vG+sRAHe894P8M/e8XqCuMFL/t13 zt/zZx/06aD/nNmrrpdhpUFjEraH62ytrRO6ErzH7RwPJAHUE8Hawwucu/cOirTD1P4mEwcmqM7U SSarxM2YoB7hwgDnBEEgSSJJdTKkubdGZS5he2hZuX9ItmHxHrpFQX0y5uB8Ba1gmHvuPueH3azy q+tnl754/AW3sufqm/nEF7/3SLfpsfG5dz4Hk42QYRJnG4sf2H/jc952w4teIlT+XUTjeZy//x5W v/UxmuUmWiqc93jhkBIssDF0dPoeHWsm52tM7qpRqQdEsUIJh3cOUzqyUclgUDIaWgbbJfmmIckF FaUorGfblkzONtk920bJjDzrc/eZzXJxU75r9w0/+/7thQdskDT45Q9/83Hl+m+M//bWp4D3SBU1 smzrg/PHmr9843NfLBoHX4v3gqVzd3H+a3+IXjlJRXl2NBJSgtTjk9tBDoPMk5UwKjxFOfagQCA8 CAfCSSIlqQWaWqgRAvrGYALP7vkWE80mqArDLOWBMxfLpc3yg/Vdx95V9tdzIRS3ffzkE+z+W33/ 9//uH7F45xcppc1dvfnNTmcl3hpy88TcUVWpTdKYmGdyz1H6ecHGxiquSBmL1kfInSfWglokqGtJ I5A0A0Ur1EyEARNRwEQcMZGENKMArSSpNQx8RmPCcWhPlXqlAiqkOyy578zicHWrfE9t7sj7y95q jve89U8e+tsNjr+r63HxO5/lm5/8NWQUoVpT0aB79s3tuf3/8inPes30wUPPwPdXyLsX2Fx8kKWz P6C/soDMU0IBWkiEkAi/Q8F3KrP3j1xgnad0ntwbvMqp10pmWpJaEiJ0jVI0uLxecGahuzDI/a/P 3fDST3TP3W6dLXnluz/H3MFrfzwgAKe++3m+9z/+NTII+cV3f5+P/Kt9zw3q4W8cOvT0n7l6783U vcX2Vyn7S6T9Vba3N+j1BmTDHJNafMl44/hx7I1/HEiLCixxVFKPDbXIEWox7rLIiM004cyytQ9v FF82MvqNrTOLd+556k04U/KW/3zf32fuj/4W5U/f/2x0VGdt+bvoida0K/uvr1dab9o7eeDQ3uo0 NZsjbA9kitAlXnssntKNV975cWdSlDmqGKFthjIFvizHqdw6jBVsDRVXNhRLm+rkyOiPBPXp/1r2 lrf3nXgpg9WLvOF3vvFD7fyxPqr5n596M6e+/xHas8e5cPeD7Hvy/qPejV5d1eErp8Lq1bNJRU0k AdXq+Mx9fBStkKFESI8rCtxwhBsOcWmKTTPSYcHWtmFtE1Y2RbExCB5IbfBpGdc+tX35wsLM0atJ t1a4+gVv4Off9qEfaeOP/ZkTwJ/+wQuoNg6yuvBVFs9eYPfRQ/OuHD5bY19UkfLmZhTsbSRhtVGJ RFKJiCKNlODygqKfMtxM6W0UbHZL192yg+2BuJwZfYdX0RfCavtbnYdOrc9efYAXvONj3PXnv80v feAvf2zbfiIgj4xPf/SF1FtXsXLpK2SDZeZveKPqnP7cvC9G1ylrr9feHteSvVKKtvAitKUn3XZF r+M7w22xUJbqFCK4Vye1U7f89pnl29/W9LXJeW565Tu5dMfned37/vwntun/AFoS/oYm/3uWAAAA JXRFWHRkYXRlOmNyZWF0ZQAyMDIyLTAzLTMxVDA1OjMxOjI5KzAwOjAwwNofTAAAACV0RVh0ZGF0 ZTptb2RpZnkAMjAyMi0wMy0zMVQwNTozMToyOSswMDowMLGHp/AAAAAASUVORK5CYII='/%3E%3C/svg%3E";
@@ -0,0 +1,69 @@
+use super::*;
+use crate::stake_events::EmergencyWithdraw;
+use near_sdk::assert_one_yocto;
+
+//Call Methods
+#[near_bindgen]
+impl Contract {
+    /// Lets stakers take their NEKO back without the factory while `enabled`.
+    pub fn set_emergency_mode(&mut self, enabled: bool) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_set_emergency_mode(enabled);
+    }
+    /// Returns the whole stake of the caller from the staking reserve and forfeits
+    /// its rewards, only available in emergency mode. What the reserve can't cover
+    /// is queued as shortfall. The factory is never called, the cookies baked against
+    /// the stake stay with the staker but can't be exchanged anymore since unstaking
+    /// needs the stake record removed here.
+    #[payable]
+    pub fn emergency_withdraw(&mut self) -> U128 {
+        assert_one_yocto();
+        require!(self.emergency_mode, "Emergency mode is not active");
+        let account_id = env::predecessor_account_id();
+        self.internal_upgrade_stake(&account_id);
+        let mut stake_data = self
+            .stake
+            .remove(&account_id)
+            .unwrap_or_else(|| panic!("No stake record found for this account"));
+        require!(
+            stake_data
+                .positions
+                .iter()
+                .all(|position| !position.exiting),
+            "A lock position exit is still in progress"
+        );
+        self.checkpoint_reward();
+        stake_data.update_reward(&self.reward_index);
+        let forfeited_reward = stake_data.acc_reward;
+        stake_data.acc_reward = 0;
+        self.reward_index.total_shares -= stake_data.shares;
+        self.total_staked -= stake_data.total_stake;
+
+        let amount = std::cmp::min(stake_data.total_stake, self.staking_reserve);
+        self.staking_reserve -= amount;
+        self.internal_queue_shortfall(&account_id, stake_data.total_stake - amount);
+        if amount > 0 {
+            self.token.internal_transfer(
+                &env::current_account_id(),
+                &account_id,
+                amount,
+                Some("Emergency withdraw".to_string()),
+            );
+        }
+        EmergencyWithdraw {
+            account_id: &account_id,
+            amount: &U128(amount),
+            forfeited_reward: &U128(forfeited_reward),
+        }
+        .emit();
+        U128(amount)
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_set_emergency_mode(&mut self, enabled: bool) {
+        self.emergency_mode = enabled;
+        log!("Emergency mode set to {}", enabled);
+    }
+}
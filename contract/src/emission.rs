@@ -0,0 +1,132 @@
+use super::*;
+use crate::stake::{RewardIndex, NANOS_PER_MINUTE, REWARD_PRECISION};
+use serde::{Deserialize, Serialize};
+
+/// Emission period starting at `start_time`, `reward_rate` is percent of
+/// stake per minute and stays in force until the next epoch starts.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Epoch {
+    pub start_time: u64,
+    pub reward_rate: u8,
+}
+
+/// Published cookie emission schedule of the NEKO stake. Inside an epoch the
+/// rate halves every `halving_interval` nanoseconds (0 disables halving) and
+/// the total emission never exceeds `max_emission`.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Debug, Clone)]
+pub struct EmissionSchedule {
+    pub epochs: Vec<Epoch>,
+    pub halving_interval: u64,
+    pub max_emission: Option<Balance>,
+    pub total_emitted: Balance,
+}
+impl EmissionSchedule {
+    pub fn new(reward_rate: u8) -> Self {
+        Self {
+            epochs: vec![Epoch {
+                start_time: env::block_timestamp(),
+                reward_rate,
+            }],
+            halving_interval: 0,
+            max_emission: None,
+            total_emitted: 0,
+        }
+    }
+    pub fn assert_valid(&self) {
+        require!(!self.epochs.is_empty(), "Emission schedule has no epochs");
+        require!(
+            self.epochs
+                .windows(2)
+                .all(|pair| pair[0].start_time < pair[1].start_time),
+            "Epochs must be sorted by start time"
+        );
+        if let Some(max_emission) = self.max_emission {
+            require!(
+                max_emission >= self.total_emitted,
+                "Max emission is lower than the emitted amount"
+            );
+        }
+    }
+    /// Effective reward rate at `time`, halvings included.
+    pub fn rate_at(&self, time: u64) -> u8 {
+        match self
+            .epochs
+            .iter()
+            .rev()
+            .find(|epoch| epoch.start_time <= time)
+        {
+            Some(epoch) => {
+                let halvings = (time - epoch.start_time)
+                    .checked_div(self.halving_interval)
+                    .unwrap_or(0);
+                epoch.reward_rate.checked_shr(halvings as u32).unwrap_or(0)
+            }
+            None => 0,
+        }
+    }
+    /// Reward per share emitted between `from` and `to`, integrated over every
+    /// epoch and halving boundary in between.
+    fn integrate(&self, from: u64, to: u64) -> u128 {
+        let mut reward_per_share: u128 = 0;
+        for (i, epoch) in self.epochs.iter().enumerate() {
+            let epoch_end = self
+                .epochs
+                .get(i + 1)
+                .map(|next| next.start_time)
+                .unwrap_or(u64::MAX);
+            let start = std::cmp::max(from, epoch.start_time);
+            let end = std::cmp::min(to, epoch_end);
+            if start >= end {
+                continue;
+            }
+            let mut time = start;
+            while time < end {
+                let (halvings, period_end) =
+                    match (time - epoch.start_time).checked_div(self.halving_interval) {
+                        Some(halvings) => {
+                            let period_end = epoch.start_time.saturating_add(
+                                (halvings + 1).saturating_mul(self.halving_interval),
+                            );
+                            (halvings, std::cmp::min(end, period_end))
+                        }
+                        None => (0, end),
+                    };
+                if halvings >= 128 {
+                    break;
+                }
+                let rate_per_minute = epoch.reward_rate as u128 * REWARD_PRECISION / 100;
+                reward_per_share += (rate_per_minute * (period_end - time) as u128
+                    / NANOS_PER_MINUTE as u128)
+                    >> halvings;
+                time = period_end;
+            }
+        }
+        reward_per_share
+    }
+    /// Reward per share and emission since the last checkpoint of `index`,
+    /// limited to the emission left under the cap.
+    fn pending(&self, index: &RewardIndex) -> (u128, u64, Balance) {
+        let minutes = (env::block_timestamp() - index.last_update_time) / NANOS_PER_MINUTE;
+        let end = index.last_update_time + minutes * NANOS_PER_MINUTE;
+        let mut reward_per_share = self.integrate(index.last_update_time, end);
+        let mut emitted = reward_per_share * index.total_shares / REWARD_PRECISION;
+        if let Some(max_emission) = self.max_emission {
+            let remaining = max_emission - self.total_emitted;
+            if emitted > remaining {
+                reward_per_share = remaining * REWARD_PRECISION / index.total_shares;
+                emitted = remaining;
+            }
+        }
+        (reward_per_share, end, emitted)
+    }
+    /// Reward per share of `index` as of now.
+    pub fn accumulated(&self, index: &RewardIndex) -> u128 {
+        index.acc_reward_per_share + self.pending(index).0
+    }
+    pub fn checkpoint(&mut self, index: &mut RewardIndex) {
+        let (reward_per_share, end, emitted) = self.pending(index);
+        index.acc_reward_per_share += reward_per_share;
+        index.last_update_time = end;
+        self.total_emitted += emitted;
+    }
+}
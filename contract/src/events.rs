@@ -0,0 +1,159 @@
+//! Contract events that are not covered by nep141, logged in the nep-297 format:
+//! <https://github.com/near/NEPs/blob/master/specs/Standards/EventsFormat.md>
+
+use crate::access::Role;
+use crate::multisig::MultisigAction;
+use crate::pause::Feature;
+use crate::timelock::QueuedChange;
+use near_sdk::json_types::U128;
+use near_sdk::{env, AccountId};
+use serde::Serialize;
+
+pub const NEKO_EVENT_STANDARD: &str = "neko";
+pub const NEKO_EVENT_VERSION: &str = "1.0.0";
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+enum NekoEventKind<'a> {
+    RoleGranted(&'a [RoleChange<'a>]),
+    RoleRevoked(&'a [RoleChange<'a>]),
+    MinterAllowanceSet(&'a [MinterAllowanceSet<'a>]),
+    PauseChanged(&'a [PauseChange<'a>]),
+    OwnerProposed(&'a [OwnerChange<'a>]),
+    OwnerProposalCancelled(&'a [OwnerChange<'a>]),
+    OwnerTransferred(&'a [OwnerChange<'a>]),
+    ChangeQueued(&'a [TimelockChange<'a>]),
+    ChangeExecuted(&'a [TimelockChange<'a>]),
+    ChangeCancelled(&'a [TimelockChange<'a>]),
+    ProposalCreated(&'a [MultisigProposal<'a>]),
+    ProposalConfirmed(&'a [MultisigProposal<'a>]),
+    ProposalExecuted(&'a [MultisigProposal<'a>]),
+    ProposalExpired(&'a [MultisigProposal<'a>]),
+}
+
+#[derive(Serialize, Debug)]
+struct NekoEvent<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event_kind: NekoEventKind<'a>,
+}
+
+fn emit(event_kind: NekoEventKind) {
+    let event = NekoEvent {
+        standard: NEKO_EVENT_STANDARD,
+        version: NEKO_EVENT_VERSION,
+        event_kind,
+    };
+    // Events cannot fail to serialize so fine to panic on error
+    let json = serde_json::to_string(&event).unwrap_or_else(|_| env::abort());
+    env::log_str(&format!("EVENT_JSON:{}", json));
+}
+
+/// Data to log when `role` was granted to or revoked from `account_id` by `by`.
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RoleChange<'a> {
+    pub account_id: &'a AccountId,
+    pub role: &'a Role,
+    pub by: &'a AccountId,
+}
+impl RoleChange<'_> {
+    pub fn emit_granted(self) {
+        emit(NekoEventKind::RoleGranted(&[self]))
+    }
+    pub fn emit_revoked(self) {
+        emit(NekoEventKind::RoleRevoked(&[self]))
+    }
+}
+
+/// Data to log when the mint allowance of `account_id` was set.
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MinterAllowanceSet<'a> {
+    pub account_id: &'a AccountId,
+    pub allowance: &'a U128,
+}
+impl MinterAllowanceSet<'_> {
+    pub fn emit(self) {
+        emit(NekoEventKind::MinterAllowanceSet(&[self]))
+    }
+}
+
+/// Data to log when `feature` was paused or unpaused by `by`.
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct PauseChange<'a> {
+    pub feature: &'a Feature,
+    pub paused: bool,
+    pub by: &'a AccountId,
+}
+impl PauseChange<'_> {
+    pub fn emit(self) {
+        emit(NekoEventKind::PauseChanged(&[self]))
+    }
+}
+
+/// Data to log when the owner changes or a new owner is proposed. `new_owner_id` is
+/// `None` when ownership was renounced or a proposal was cancelled.
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct OwnerChange<'a> {
+    pub old_owner_id: Option<&'a AccountId>,
+    pub new_owner_id: Option<&'a AccountId>,
+}
+impl OwnerChange<'_> {
+    pub fn emit_proposed(self) {
+        emit(NekoEventKind::OwnerProposed(&[self]))
+    }
+    pub fn emit_proposal_cancelled(self) {
+        emit(NekoEventKind::OwnerProposalCancelled(&[self]))
+    }
+    pub fn emit_transferred(self) {
+        emit(NekoEventKind::OwnerTransferred(&[self]))
+    }
+}
+
+/// Data to log when a timelocked parameter change was queued, executed or cancelled.
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct TimelockChange<'a> {
+    #[serde(flatten)]
+    pub change: &'a QueuedChange,
+}
+impl TimelockChange<'_> {
+    pub fn emit_queued(self) {
+        emit(NekoEventKind::ChangeQueued(&[self]))
+    }
+    pub fn emit_executed(self) {
+        emit(NekoEventKind::ChangeExecuted(&[self]))
+    }
+    pub fn emit_cancelled(self) {
+        emit(NekoEventKind::ChangeCancelled(&[self]))
+    }
+}
+
+/// Data to log when `account_id` created, confirmed, executed or expired multisig
+/// proposal `proposal_id`, `action` is only logged on creation.
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MultisigProposal<'a> {
+    pub proposal_id: u64,
+    pub account_id: &'a AccountId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<&'a MultisigAction>,
+}
+impl MultisigProposal<'_> {
+    pub fn emit_created(self) {
+        emit(NekoEventKind::ProposalCreated(&[self]))
+    }
+    pub fn emit_confirmed(self) {
+        emit(NekoEventKind::ProposalConfirmed(&[self]))
+    }
+    pub fn emit_executed(self) {
+        emit(NekoEventKind::ProposalExecuted(&[self]))
+    }
+    pub fn emit_expired(self) {
+        emit(NekoEventKind::ProposalExpired(&[self]))
+    }
+}
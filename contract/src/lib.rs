@@ -13,46 +13,130 @@
 
 // To conserve gas, efficient serialization is achieved through Borsh (http://borsh.io/)
 use crate::constants::*;
+use access::{Role, OWNER_ROLES};
+use config::Config;
+use emission::{EmissionSchedule, Epoch};
+use lockup::LockTier;
+use migration::LegacyStakes;
+use multisig::Multisig;
+use near_contract_standards::fungible_token::core::FungibleTokenCore;
 use near_contract_standards::fungible_token::metadata::{
     FungibleTokenMetadata, FungibleTokenMetadataProvider, FT_METADATA_SPEC,
 };
+use near_contract_standards::fungible_token::resolver::FungibleTokenResolver;
+use near_contract_standards::fungible_token::tax::TaxPolicy;
 use near_contract_standards::fungible_token::FungibleToken;
+use near_contract_standards::upgrade::Upgrade;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, UnorderedMap};
+use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap};
 use near_sdk::json_types::Base64VecU8;
 use near_sdk::json_types::U128;
-use near_sdk::Promise;
 use near_sdk::{
-    env, log, near_bindgen, require, AccountId, Balance, BorshStorageKey, PanicOnDefault,
+    env, log, near_bindgen, require, AccountId, Balance, BorshStorageKey, Gas, PanicOnDefault,
     PromiseOrValue,
 };
-use stake::Stake;
+use nft_stake::{NftCollection, NftStaker};
+use pause::{Feature, PauseFlags};
+use pool::Pool;
+use serde::{Deserialize, Serialize};
+use stake::{RewardIndex, StakeV2};
+use timelock::QueuedChange;
+use tokens::SupplyAudit;
+use unbonding::UnbondingEntry;
+mod access;
 mod accounts;
+mod compound;
+mod config;
 mod constants;
+mod emergency;
+mod emission;
+mod events;
+mod lockup;
+mod maintenance;
+mod migration;
+mod multisig;
+mod nft_stake;
 mod owner;
+mod pause;
+mod pool;
+mod reserve;
 mod stake;
+mod stake_events;
+mod timelock;
 mod tokens;
+mod unbonding;
+mod upgrade;
 mod view;
-/* const contract_version: &str = "0.0.1"; */
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct Contract {
     token: FungibleToken,
     metadata: LazyOption<FungibleTokenMetadata>,
-    owner_id: AccountId,
-    stake: UnorderedMap<AccountId, Stake>,
-
-    //config
-    factory_id: AccountId,
-    vault_id: AccountId,
-    fee_percent: u8,
-    cookie_reward_rate: u8,
+    /// `None` once ownership was renounced.
+    owner_id: Option<AccountId>,
+    pending_owner_id: Option<AccountId>,
+    stake: UnorderedMap<AccountId, StakeV2>,
+    /// Stake records of the baseline release that were not upgraded yet.
+    legacy_stakes: LegacyStakes,
+    reward_index: RewardIndex,
+    unbonding: LookupMap<AccountId, Vec<UnbondingEntry>>,
+    pools: UnorderedMap<AccountId, Pool>,
+    nft_collections: UnorderedMap<AccountId, NftCollection>,
+    nft_stake: UnorderedMap<AccountId, NftStaker>,
+    roles: UnorderedMap<AccountId, Vec<Role>>,
+    /// NEKO each minter may still mint.
+    minter_allowances: LookupMap<AccountId, Balance>,
+    supply_audit: SupplyAudit,
+    /// NEKO held against staked NEKO, unstaking is paid out of it.
+    staking_reserve: Balance,
+    /// Principal of all main stakes.
+    total_staked: Balance,
+    /// Redemptions the reserve couldn't cover, paid once it is refilled.
+    shortfall: LookupMap<AccountId, Balance>,
+    total_shortfall: Balance,
+    paused: PauseFlags,
+    /// Stakers withdraw through `emergency_withdraw` while set.
+    emergency_mode: bool,
+    /// Minimum nanoseconds between queueing and executing a parameter change.
+    timelock_delay: u64,
+    queued_changes: UnorderedMap<u64, QueuedChange>,
+    next_change_id: u64,
+    multisig: Multisig,
+    /// Live collections can only be cleared while set.
+    maintenance_mode: bool,
+    /// Staged code, its owner follows `owner_id`.
+    upgrade: Upgrade,
+
+    config: Config,
+    emission: EmissionSchedule,
 }
-#[derive(BorshSerialize, BorshStorageKey)]
-enum StorageKey {
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    BorshStorageKey,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageKey {
     FungibleToken,
     Metadata,
     StakeData,
+    Pools,
+    PoolStake { token_id: AccountId },
+    NftCollections,
+    NftStakeData,
+    Unbonding,
+    Roles,
+    MinterAllowances,
+    Shortfall,
+    QueuedChanges,
+    MultisigSigners,
+    MultisigProposals,
+    StakeDataV2,
 }
 
 #[near_bindgen]
@@ -62,7 +146,7 @@ impl Contract {
         owner_id: AccountId,
         vault_id: AccountId,
         factory_id: AccountId,
-        fee_percent: u8,
+        bake_fee_bps: u16,
         cookie_reward_rate: u8,
     ) -> Self {
         Self::new(
@@ -78,7 +162,7 @@ impl Contract {
             },
             vault_id,
             factory_id,
-            fee_percent,
+            bake_fee_bps,
             cookie_reward_rate,
         )
     }
@@ -88,39 +172,32 @@ impl Contract {
         metadata: FungibleTokenMetadata,
         vault_id: AccountId,
         factory_id: AccountId,
-        fee_percent: u8,
+        bake_fee_bps: u16,
         cookie_reward_rate: u8,
     ) -> Self {
         require!(!env::state_exists(), "Already initialized");
         metadata.assert_valid();
-        let mut this = Self {
-            owner_id: owner_id.clone(),
-            token: FungibleToken::new(StorageKey::FungibleToken),
-            metadata: LazyOption::new(StorageKey::Metadata, Some(&metadata)),
-            stake: UnorderedMap::new(StorageKey::StakeData.try_to_vec().unwrap()),
-            factory_id: factory_id.clone(),
-            vault_id: vault_id.clone(),
-            fee_percent,
-            cookie_reward_rate,
-        };
+        let mut this = Self::internal_new(
+            owner_id.clone(),
+            FungibleToken::new(StorageKey::FungibleToken),
+            LazyOption::new(StorageKey::Metadata, Some(&metadata)),
+            Config::new(factory_id.clone(), vault_id.clone(), bake_fee_bps),
+            EmissionSchedule::new(cookie_reward_rate),
+        );
+        this.config.assert_reward_rate(cookie_reward_rate);
         this.token.vault = vault_id.clone();
         this.token.internal_register_account(&owner_id);
-        this.token.internal_register_account(&vault_id.clone());
+        this.token.internal_register_account(&vault_id);
         this.token.internal_register_account(&factory_id);
-        if this.owner_id != env::current_account_id() {
+        if owner_id != env::current_account_id() {
             this.token
                 .internal_register_account(&env::current_account_id());
         }
+        Self::write_state_version();
 
         this
     }
 
-    pub fn clean(keys: Vec<Base64VecU8>) {
-        for key in keys.iter() {
-            env::storage_remove(&key.0);
-        }
-    }
-
     fn on_account_closed(&mut self, account_id: AccountId, balance: Balance) {
         log!("Closed @{} with {}", account_id, balance);
     }
@@ -128,20 +205,68 @@ impl Contract {
     fn on_tokens_burned(&mut self, account_id: AccountId, amount: Balance) {
         log!("Account @{} burned {}", account_id, amount);
     }
-    pub fn update_vault(&mut self, vault_id: AccountId) {
-        self.assert_owner(env::signer_account_id());
-        self.token.vault = vault_id.clone();
-        self.token.internal_register_account(&vault_id.clone());
-        env::log_str("update vault");
-    }
     pub fn assert_owner(&self, account_id: AccountId) {
-        assert_eq!(self.owner_id, account_id, "Assert owner failed");
+        assert_eq!(self.owner_id, Some(account_id), "Assert owner failed");
     }
     pub fn register_account(&mut self, account_id: AccountId) {
         self.token.internal_register_account(&account_id);
     }
 }
-near_contract_standards::impl_fungible_token_core!(Contract, token, on_tokens_burned);
+
+impl Contract {
+    /// State shared by `new` and `migrate`, everything but the token and the config starts empty.
+    fn internal_new(
+        owner_id: AccountId,
+        token: FungibleToken,
+        metadata: LazyOption<FungibleTokenMetadata>,
+        config: Config,
+        emission: EmissionSchedule,
+    ) -> Self {
+        let mut this = Self {
+            owner_id: Some(owner_id.clone()),
+            pending_owner_id: None,
+            token,
+            metadata,
+            stake: UnorderedMap::new(StorageKey::StakeDataV2),
+            legacy_stakes: LegacyStakes::new(),
+            reward_index: RewardIndex::default(),
+            unbonding: LookupMap::new(StorageKey::Unbonding),
+            pools: UnorderedMap::new(StorageKey::Pools),
+            nft_collections: UnorderedMap::new(StorageKey::NftCollections),
+            nft_stake: UnorderedMap::new(StorageKey::NftStakeData),
+            roles: UnorderedMap::new(StorageKey::Roles),
+            minter_allowances: LookupMap::new(StorageKey::MinterAllowances),
+            supply_audit: SupplyAudit::default(),
+            staking_reserve: 0,
+            total_staked: 0,
+            shortfall: LookupMap::new(StorageKey::Shortfall),
+            total_shortfall: 0,
+            paused: PauseFlags::default(),
+            emergency_mode: false,
+            timelock_delay: 0,
+            queued_changes: UnorderedMap::new(StorageKey::QueuedChanges),
+            next_change_id: 0,
+            multisig: Multisig::new(),
+            maintenance_mode: false,
+            upgrade: Upgrade::new(owner_id.clone(), STAGING_DURATION),
+            config,
+            emission,
+        };
+        for role in OWNER_ROLES {
+            this.internal_grant_role(&owner_id, role);
+        }
+        //Moves between the contract, the vault and the factory are never taxed
+        for account_id in [
+            env::current_account_id(),
+            this.config.vault_id.clone(),
+            this.config.factory_id.clone(),
+        ] {
+            this.token.tax_exemptions.senders.insert(&account_id);
+            this.token.tax_exemptions.receivers.insert(&account_id);
+        }
+        this
+    }
+}
 near_contract_standards::impl_fungible_token_storage!(Contract, token, on_account_closed);
 #[near_bindgen]
 impl FungibleTokenMetadataProvider for Contract {
@@ -153,10 +278,14 @@ impl FungibleTokenMetadataProvider for Contract {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::convert::TryInto;
+    use crate::multisig::MultisigAction;
+    use crate::stake::StakeV1;
+    use crate::timelock::ParamChange;
+    use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+    use near_contract_standards::non_fungible_token::core::NonFungibleTokenReceiver;
 
     use near_sdk::test_utils::{accounts, VMContextBuilder};
-    use near_sdk::{testing_env, Gas, ONE_NEAR};
+    use near_sdk::{testing_env, PromiseResult, RuntimeFeesConfig, VMConfig, VMContext};
 
     // mock the context for testing, notice "signer_account_id" that was accessed above from env::
     fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
@@ -168,6 +297,70 @@ mod tests {
         builder
     }
 
+    fn with_promise_result(context: VMContext, result: PromiseResult) {
+        testing_env!(
+            context,
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+    }
+
+    fn balance_result(amount: Balance) -> PromiseResult {
+        PromiseResult::Successful(serde_json::to_vec(&amount).unwrap())
+    }
+
+    /// Queues and executes `change` right away, the timelock delay is 0 by default.
+    fn apply_change(contract: &mut Contract, change: ParamChange) {
+        let id = contract.queue_change(change, env::block_timestamp());
+        contract.execute_change(id);
+    }
+
+    // stakes NEKO of accounts(1) through `stake` and a successful bake
+    fn stake_neko(
+        contract: &mut Contract,
+        context: &mut VMContextBuilder,
+        amount: Balance,
+        lock_tier: Option<u8>,
+    ) {
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(2)
+            .build());
+        contract.stake(amount, lock_tier);
+        with_promise_result(
+            context
+                .predecessor_account_id(accounts(0))
+                .attached_deposit(1)
+                .build(),
+            balance_result(amount),
+        );
+        contract.neko_stake_call_back(accounts(1), amount, 0, lock_tier);
+    }
+
+    // unstakes flexible NEKO of accounts(1) through `unstake` and an exchange with `result`
+    fn unstake_neko(
+        contract: &mut Contract,
+        context: &mut VMContextBuilder,
+        amount: Balance,
+        result: PromiseResult,
+    ) {
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.unstake(amount);
+        with_promise_result(
+            context
+                .predecessor_account_id(accounts(0))
+                .attached_deposit(1)
+                .build(),
+            result,
+        );
+        contract.cookie_exchange_call_back(accounts(1), amount);
+    }
+
     #[test]
     fn mint_and_transfer_test() {
         let mut context = get_context(accounts(0));
@@ -180,17 +373,233 @@ mod tests {
             accounts(0),
             "vault.testnet".parse().unwrap(),
             "factory.testnet".parse().unwrap(),
-            5,
+            500,
             5,
         );
-        contract.ft_mint(accounts(0), 10_000_000_000);
+        contract.grant_role(accounts(0), Role::Minter);
+        contract.set_minter_allowance(accounts(0), U128(10_000_000_000));
+        contract.ft_mint(accounts(2), 10_000_000_000);
         contract.ft_mint(accounts(1), 0);
+        assert_eq!(contract.get_minter_allowance(accounts(0)).0, 0);
 
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
         contract.ft_transfer(accounts(1), U128(10_000_000_000), None);
         let a = contract.ft_balance_of(accounts(1));
         assert_eq!(a.0, 9500000000);
     }
     #[test]
+    fn test_tax_policy() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        apply_change(
+            &mut contract,
+            ParamChange::TaxPolicy(TaxPolicy {
+                rate_bps: 1_000,
+                burn_share_bps: 2_000,
+                vault_share_bps: 4_000,
+                staker_share_bps: 4_000,
+            }),
+        );
+        assert_eq!(contract.get_tax_policy().rate_bps, 1_000);
+        contract.ft_internal_mint(&accounts(1), 0);
+        contract.ft_internal_mint(&accounts(2), 1000);
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.ft_transfer(accounts(1), U128(1000), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 900);
+        assert_eq!(
+            contract.ft_balance_of("vault.testnet".parse().unwrap()).0,
+            40
+        );
+        assert_eq!(contract.ft_balance_of(accounts(0)).0, 40);
+        //The staker share backs the reserve instead of sitting in the contract balance
+        assert_eq!(contract.get_reserve().reserve.0, 40);
+        assert_eq!(contract.get_supply_stats().total_to_stakers.0, 40);
+    }
+    #[test]
+    fn test_tax_exemptions() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        let vault_id: AccountId = "vault.testnet".parse().unwrap();
+        assert!(contract.get_tax_exempt_receivers(0, 10).contains(&vault_id));
+        contract.set_tax_exempt_receiver(accounts(3), true);
+        contract.set_custom_tax_rate(accounts(4), Some(100));
+        assert_eq!(
+            contract.get_custom_tax_rates(0, 10),
+            vec![(accounts(4), 100)]
+        );
+        contract.ft_internal_mint(&accounts(1), 0);
+        contract.ft_internal_mint(&accounts(3), 0);
+        contract.ft_internal_mint(&accounts(4), 0);
+        contract.ft_internal_mint(&accounts(2), 3000);
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.ft_transfer(vault_id.clone(), U128(1000), None);
+        assert_eq!(contract.ft_balance_of(vault_id).0, 1000);
+        contract.ft_transfer(accounts(3), U128(1000), None);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 1000);
+        contract.ft_transfer(accounts(4), U128(1000), None);
+        assert_eq!(contract.ft_balance_of(accounts(4)).0, 990);
+    }
+    #[test]
+    fn test_supply_audit() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.ft_internal_mint(&accounts(1), 0);
+        contract.ft_internal_mint(&accounts(2), 1001);
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.ft_transfer(accounts(1), U128(1001), None);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, 0);
+        let stats = contract.get_supply_stats();
+        assert_eq!(stats.total_supply.0, 976);
+        assert_eq!(stats.total_taxed.0, 50);
+        assert_eq!(stats.total_burned.0, 25);
+        assert_eq!(stats.total_to_vault.0, 25);
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        let report = contract.ft_audit_supply(0, 3);
+        assert_eq!(report.accounts, 5);
+        assert!(!report.complete);
+        let report = contract.ft_audit_supply(report.next_index, 3);
+        assert!(report.complete);
+        assert_eq!(report.balance_sum.0, 976);
+        assert_eq!(report.difference.0, 0);
+    }
+    // contract with a 5% tax sent to the vault, accounts(2) holds 3000 and accounts(3) 0
+    fn setup_transfer_call(context: &mut VMContextBuilder) -> Contract {
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        apply_change(
+            &mut contract,
+            ParamChange::TaxPolicy(TaxPolicy {
+                rate_bps: 500,
+                burn_share_bps: 0,
+                vault_share_bps: 10_000,
+                staker_share_bps: 0,
+            }),
+        );
+        contract.ft_internal_mint(&accounts(2), 3000);
+        contract.ft_internal_mint(&accounts(3), 0);
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.ft_transfer_call(accounts(3), U128(1000), None, "".to_string());
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, 2000);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 950);
+        assert_eq!(
+            contract.ft_balance_of("vault.testnet".parse().unwrap()).0,
+            50
+        );
+        contract
+    }
+    #[test]
+    fn test_transfer_call_partial_use() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_transfer_call(&mut context);
+
+        with_promise_result(
+            context.predecessor_account_id(accounts(0)).build(),
+            PromiseResult::Successful(serde_json::to_vec(&U128(300)).unwrap()),
+        );
+        let used = contract.ft_resolve_transfer(accounts(2), accounts(3), U128(950));
+        assert_eq!(used.0, 650);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, 2300);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 650);
+    }
+    #[test]
+    fn test_transfer_call_refund() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_transfer_call(&mut context);
+
+        //The receiver failed, everything it received goes back but the tax is kept
+        with_promise_result(
+            context.predecessor_account_id(accounts(0)).build(),
+            PromiseResult::Failed,
+        );
+        let used = contract.ft_resolve_transfer(accounts(2), accounts(3), U128(950));
+        assert_eq!(used.0, 0);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, 2950);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 0);
+    }
+    #[test]
+    fn test_transfer_call_deleted_sender() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_transfer_call(&mut context);
+        let total_supply = contract.ft_total_supply().0;
+        contract.token.accounts.remove(&accounts(2));
+
+        with_promise_result(
+            context.predecessor_account_id(accounts(0)).build(),
+            PromiseResult::Successful(serde_json::to_vec(&U128(400)).unwrap()),
+        );
+        let used = contract.ft_resolve_transfer(accounts(2), accounts(3), U128(950));
+        assert_eq!(used.0, 950);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 550);
+        assert_eq!(contract.ft_total_supply().0, total_supply - 400);
+    }
+    #[test]
+    #[should_panic(expected = "Tax rate exceeds the maximum")]
+    fn test_tax_policy_bounds() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        apply_change(
+            &mut contract,
+            ParamChange::TaxPolicy(TaxPolicy {
+                rate_bps: 2_000,
+                ..TaxPolicy::default()
+            }),
+        );
+    }
+    #[test]
     fn test_views() {
         let mut context = get_context(accounts(0));
         testing_env!(context
@@ -202,13 +611,23 @@ mod tests {
             accounts(0),
             "vault.testnet".parse().unwrap(),
             "factory.testnet".parse().unwrap(),
+            500,
             5,
-            5,
         );
-        println!("Fee rate:{:?}", contract.get_fee_rate());
-        assert!(contract.get_fee_rate() == 5);
-        println!("Cookie reward rate:{:?}", contract.get_reward_rate());
-        assert!(contract.get_reward_rate() == 5);
+        let config = contract.get_config();
+        assert_eq!(config.bake_fee_bps, 500);
+        assert_eq!(
+            config.factory_id,
+            "factory.testnet".parse::<AccountId>().unwrap()
+        );
+        assert_eq!(
+            config.vault_id,
+            "vault.testnet".parse::<AccountId>().unwrap()
+        );
+        assert_eq!(contract.get_reward_rate(), 5);
+        assert_eq!(contract.get_owner(), Some(accounts(0)));
+        assert_eq!(contract.get_pending_owner(), None);
+        assert_eq!(contract.get_roles(accounts(0)), OWNER_ROLES.to_vec());
     }
     #[test]
     fn test_owner_methods() {
@@ -222,17 +641,15 @@ mod tests {
             accounts(0),
             "vault.testnet".parse().unwrap(),
             "factory.testnet".parse().unwrap(),
-            5,
+            500,
             5,
         );
-        assert!(contract.get_fee_rate() == 5);
-        assert!(contract.get_reward_rate() == 5);
-        contract.set_bake_fee(10);
-        contract.set_reward_rate(10);
-        println!("Fee rate:{:?}", contract.get_fee_rate());
-        assert!(contract.get_fee_rate() == 10);
-        println!("Cookie reward rate:{:?}", contract.get_reward_rate());
-        assert!(contract.get_reward_rate() == 10);
+        assert_eq!(contract.get_config().bake_fee_bps, 500);
+        assert_eq!(contract.get_reward_rate(), 5);
+        apply_change(&mut contract, ParamChange::BakeFee(1000));
+        apply_change(&mut contract, ParamChange::RewardRate(10));
+        assert_eq!(contract.get_config().bake_fee_bps, 1000);
+        assert_eq!(contract.get_reward_rate(), 10);
     }
     #[test]
     #[should_panic(expected = "Assert owner failed")]
@@ -247,10 +664,1643 @@ mod tests {
             accounts(1),
             "vault.testnet".parse().unwrap(),
             "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+
+        apply_change(&mut contract, ParamChange::RewardRate(10));
+    }
+    #[test]
+    #[should_panic(expected = "Bake fee of 2500 bps exceeds the maximum of 2000 bps")]
+    fn test_bake_fee_bound() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        apply_change(
+            &mut contract,
+            ParamChange::ConfigBounds {
+                max_bake_fee_bps: 3000,
+                max_reward_rate: 10,
+            },
+        );
+        let id = contract.queue_change(ParamChange::BakeFee(2500), env::block_timestamp());
+        apply_change(
+            &mut contract,
+            ParamChange::ConfigBounds {
+                max_bake_fee_bps: 2000,
+                max_reward_rate: 10,
+            },
+        );
+        contract.execute_change(id);
+    }
+    #[test]
+    #[should_panic(expected = "Reward rate of 11% per minute exceeds the maximum of 10%")]
+    fn test_reward_rate_bound() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            11,
+        );
+    }
+    #[test]
+    fn test_clear_storage() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        //Stake left behind by a pool that no longer exists
+        let prefix = StorageKey::PoolStake {
+            token_id: accounts(3),
+        };
+        let key = [prefix.try_to_vec().unwrap(), b"key".to_vec()].concat();
+        env::storage_write(&key, b"value");
+        let report = contract.clear_storage(prefix, vec![Base64VecU8(key.clone())]);
+        assert_eq!(report.keys_removed, 1);
+        assert!(report.storage_freed > 0);
+        assert!(!env::storage_has_key(&key));
+
+        //Legacy stake records are left behind once all of them were upgraded
+        let key = [StorageKey::StakeData.try_to_vec().unwrap(), b"key".to_vec()].concat();
+        env::storage_write(&key, b"value");
+        let report = contract.clear_storage(StorageKey::StakeData, vec![Base64VecU8(key)]);
+        assert_eq!(report.keys_removed, 1);
+    }
+    #[test]
+    #[should_panic(expected = "Collection is live, enter maintenance mode first")]
+    fn test_clear_storage_live_collection() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.clear_storage(StorageKey::Roles, vec![]);
+    }
+    #[test]
+    fn test_clear_storage_live_collection_in_maintenance() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        for feature in [
+            Feature::Transfers,
+            Feature::Staking,
+            Feature::Claiming,
+            Feature::Unstaking,
+            Feature::Minting,
+        ] {
+            contract.set_paused(feature, true);
+        }
+        contract.set_maintenance_mode(true);
+        assert!(contract.is_maintenance_mode());
+        //A testnet reset can clear collections in use once maintenance started
+        let key = [
+            StorageKey::StakeDataV2.try_to_vec().unwrap(),
+            b"key".to_vec(),
+        ]
+        .concat();
+        env::storage_write(&key, b"value");
+        let report =
+            contract.clear_storage(StorageKey::StakeDataV2, vec![Base64VecU8(key.clone())]);
+        assert_eq!(report.keys_removed, 1);
+        assert!(!env::storage_has_key(&key));
+    }
+    #[test]
+    #[should_panic(expected = "Collection is live, enter maintenance mode first")]
+    fn test_clear_storage_live_pool() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.add_pool(accounts(3), 5, 10);
+        contract.clear_storage(
+            StorageKey::PoolStake {
+                token_id: accounts(3),
+            },
+            vec![],
+        );
+    }
+    #[test]
+    fn test_timelock() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        apply_change(&mut contract, ParamChange::TimelockDelay(100));
+        assert_eq!(contract.get_timelock_delay(), 100);
+
+        let fee_id = contract.queue_change(ParamChange::BakeFee(1000), 100);
+        let vault_id = contract.queue_change(ParamChange::Vault(accounts(3)), 150);
+        let queued = contract.get_queued_changes(0, 10);
+        assert_eq!(queued.len(), 2);
+        assert_eq!(queued[1].change, ParamChange::Vault(accounts(3)));
+        contract.cancel_change(vault_id);
+        assert_eq!(contract.get_queued_changes(0, 10).len(), 1);
+
+        let tax_policy = TaxPolicy {
+            rate_bps: 1_000,
+            burn_share_bps: 5_000,
+            vault_share_bps: 5_000,
+            staker_share_bps: 0,
+        };
+        let tax_id = contract.queue_change(ParamChange::TaxPolicy(tax_policy.clone()), 100);
+        let penalty_id = contract.queue_change(ParamChange::EarlyExitPenalty(Some(1_000)), 100);
+        assert_eq!(contract.get_tax_policy().rate_bps, 500);
+        assert_eq!(contract.get_config().early_exit_penalty_bps, None);
+
+        testing_env!(context.block_timestamp(100).build());
+        contract.execute_change(fee_id);
+        contract.execute_change(tax_id);
+        contract.execute_change(penalty_id);
+        assert_eq!(contract.get_config().bake_fee_bps, 1000);
+        assert_eq!(contract.get_tax_policy(), tax_policy);
+        assert_eq!(contract.get_config().early_exit_penalty_bps, Some(1_000));
+        assert!(contract.get_queued_changes(0, 10).is_empty());
+    }
+    #[test]
+    #[should_panic(expected = "Timelock has not passed yet")]
+    fn test_timelock_early_execution() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
             5,
+        );
+        apply_change(&mut contract, ParamChange::TimelockDelay(100));
+        let id = contract.queue_change(ParamChange::RewardRate(10), 100);
+
+        testing_env!(context.block_timestamp(99).build());
+        contract.execute_change(id);
+    }
+    #[test]
+    fn test_multisig() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.setup_multisig(vec![accounts(1), accounts(2), accounts(3)], 2);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        let id = contract.propose(MultisigAction::Mint {
+            account_id: accounts(4),
+            amount: U128(1000),
+        });
+        assert_eq!(contract.get_proposal(id).unwrap().confirmations.len(), 1);
+        assert_eq!(contract.ft_balance_of(accounts(4)).0, 0);
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.confirm(id);
+        assert_eq!(contract.ft_balance_of(accounts(4)).0, 1000);
+        assert!(contract.get_proposal(id).is_none());
+
+        let id = contract.propose(MultisigAction::GrantRole {
+            account_id: accounts(4),
+            role: Role::Pauser,
+        });
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.confirm(id);
+        assert!(contract.has_role(accounts(4), Role::Pauser));
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+
+        let id = contract.propose(MultisigAction::RemoveSigner {
+            account_id: accounts(3),
+        });
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.confirm(id);
+        assert_eq!(contract.get_multisig_signers().len(), 2);
+
+        //Stale proposals can be removed by anyone
+        contract.propose(MultisigAction::SetThreshold { threshold: 1 });
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .block_timestamp(MULTISIG_PROPOSAL_LIFETIME)
+            .build());
+        assert_eq!(contract.remove_expired_proposals(10), 1);
+        assert!(contract.get_proposals(0, 10).is_empty());
+        assert_eq!(contract.get_multisig_threshold(), 2);
+    }
+    #[test]
+    #[should_panic(expected = "Admin action requires a multisig proposal")]
+    fn test_multisig_blocks_single_key() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.setup_multisig(vec![accounts(1), accounts(2)], 2);
+        contract.queue_change(ParamChange::BakeFee(1000), env::block_timestamp());
+    }
+    #[test]
+    #[should_panic(expected = "Admin action requires a multisig proposal")]
+    fn test_multisig_blocks_single_key_role() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.setup_multisig(vec![accounts(1), accounts(2)], 2);
+        contract.grant_role(accounts(3), Role::Admin);
+    }
+    #[test]
+    fn test_owner_transfer() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.propose_owner(accounts(2));
+        contract.cancel_owner_proposal();
+        assert_eq!(contract.get_pending_owner(), None);
+        contract.propose_owner(accounts(1));
+        assert_eq!(contract.get_owner(), Some(accounts(0)));
+        assert_eq!(contract.get_pending_owner(), Some(accounts(1)));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.accept_owner();
+        assert_eq!(contract.get_owner(), Some(accounts(1)));
+        assert_eq!(contract.get_pending_owner(), None);
+        contract.set_unbonding_period(10);
+
+        contract.renounce_ownership();
+        assert_eq!(contract.get_owner(), None);
+        assert!(near_sdk::test_utils::get_logs()
+            .iter()
+            .any(|log| log.contains("owner_transferred") && log.contains("null")));
+    }
+    #[test]
+    fn test_owner_transfer_moves_roles() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.grant_role(accounts(0), Role::Minter);
+        contract.propose_owner(accounts(1));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.accept_owner();
+        for role in OWNER_ROLES {
+            assert!(!contract.has_role(accounts(0), role));
+            assert!(contract.has_role(accounts(1), role));
+        }
+        //Roles outside of the owner roles stay with the account
+        assert!(contract.has_role(accounts(0), Role::Minter));
+        assert!(!contract.has_role(accounts(1), Role::Minter));
+        contract.grant_role(accounts(2), Role::Pauser);
+        assert!(contract.has_role(accounts(2), Role::Pauser));
+    }
+    #[test]
+    fn test_renounce_ownership_revokes_roles() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.grant_role(accounts(2), Role::Admin);
+
+        contract.renounce_ownership();
+        for role in OWNER_ROLES {
+            assert!(!contract.has_role(accounts(0), role));
+        }
+        assert!(contract.has_role(accounts(2), Role::Admin));
+        assert!(near_sdk::test_utils::get_logs()
+            .iter()
+            .any(|log| log.contains("role_revoked") && log.contains("fee_manager")));
+    }
+    #[test]
+    #[should_panic(expected = "Only the pending owner can accept ownership")]
+    fn test_owner_accept_requires_proposal() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.propose_owner(accounts(1));
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.accept_owner();
+    }
+    #[test]
+    #[should_panic(expected = "Missing role Minter")]
+    fn test_mint_requires_minter() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.ft_mint(accounts(1), 1000);
+    }
+    #[test]
+    fn test_minter_allowance() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.grant_role(accounts(1), Role::Minter);
+        contract.set_minter_allowance(accounts(1), U128(1000));
+        assert!(contract.has_role(accounts(1), Role::Minter));
+        assert!(near_sdk::test_utils::get_logs()
+            .iter()
+            .any(|log| log.contains("role_granted")));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.ft_mint(accounts(2), 600);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, 600);
+        assert_eq!(contract.get_minter_allowance(accounts(1)).0, 400);
+        assert!(near_sdk::test_utils::get_logs()
+            .iter()
+            .any(|log| log.contains("ft_mint")));
+
+        //Revoking the role drops the remaining allowance
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.revoke_role(accounts(1), Role::Minter);
+        assert!(contract.get_roles(accounts(1)).is_empty());
+        assert_eq!(contract.get_minter_allowance(accounts(1)).0, 0);
+    }
+    #[test]
+    #[should_panic(expected = "Transfers is paused")]
+    fn test_pause_transfers() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.set_paused(Feature::Transfers, true);
+        contract.set_paused(Feature::Transfers, false);
+        assert!(near_sdk::test_utils::get_logs()
+            .iter()
+            .any(|log| log.contains("pause_changed")));
+        contract.ft_internal_mint(&accounts(1), 0);
+        contract.ft_internal_mint(&accounts(2), 2000);
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.ft_transfer(accounts(1), U128(1000), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 950);
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.set_paused(Feature::Transfers, true);
+        assert!(contract.is_paused(Feature::Transfers));
+        assert!(!contract.get_pause_flags().staking);
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.ft_transfer(accounts(1), U128(1000), None);
+    }
+    #[test]
+    #[should_panic(expected = "Staking is paused")]
+    fn test_pause_staking() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.set_paused(Feature::Staking, true);
+        contract.ft_internal_mint(&accounts(1), 1000);
+        stake_neko(&mut contract, &mut context, 1000, None);
+    }
+    #[test]
+    #[should_panic(expected = "Missing role Pauser")]
+    fn test_pause_requires_pauser() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_paused(Feature::Minting, true);
+    }
+    #[test]
+    fn test_owner_through_contract() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(2),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+
+        //A DAO owner acts as the predecessor of a call signed by one of its members
+        testing_env!(context
+            .signer_account_id(accounts(3))
+            .predecessor_account_id(accounts(2))
+            .build());
+        apply_change(&mut contract, ParamChange::RewardRate(10));
+        assert_eq!(contract.get_reward_rate(), 10);
+    }
+    #[test]
+    fn test_pool_stake() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(0))
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        let token_id: AccountId = "usdc.testnet".parse().unwrap();
+        contract.add_pool(token_id.clone(), 10, 5);
+
+        testing_env!(context.predecessor_account_id(token_id.clone()).build());
+        contract.ft_on_transfer(
+            accounts(1),
+            U128(1000),
+            "{\"action\":\"stake\"}".to_string(),
+        );
+        let stake_data = contract.get_pool_stake(token_id.clone(), accounts(1));
+        assert_eq!(stake_data.total_stake, 950);
+        let pool = contract.get_pool(token_id);
+        assert_eq!(pool.total_stake, 950);
+        assert_eq!(pool.fee_collected, 50);
+        assert_eq!(pool.stakers, 1);
+    }
+    #[test]
+    #[should_panic(expected = "Token is not whitelisted for staking")]
+    fn test_pool_not_whitelisted() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(0))
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        testing_env!(context
+            .predecessor_account_id("usdc.testnet".parse().unwrap())
+            .build());
+        contract.ft_on_transfer(
+            accounts(1),
+            U128(1000),
+            "{\"action\":\"stake\"}".to_string(),
+        );
+    }
+    #[test]
+    fn test_nft_stake() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(0))
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        let collection_id: AccountId = "nft.testnet".parse().unwrap();
+        contract.add_nft_collection(collection_id.clone(), U128(2));
+
+        testing_env!(context
+            .predecessor_account_id(collection_id.clone())
+            .block_timestamp(0)
+            .build());
+        contract.nft_on_transfer(accounts(1), accounts(1), "1".to_string(), "".to_string());
+        contract.nft_on_transfer(accounts(1), accounts(1), "2".to_string(), "".to_string());
+        let staked = contract.get_nft_staked(accounts(1));
+        assert_eq!(staked.len(), 2);
+        assert_eq!(staked[1].token_id, "2");
+
+        testing_env!(context.block_timestamp(10 * 60 * 1_000_000_000).build());
+        let staker = contract.get_nft_stake_by_id(accounts(1));
+        assert_eq!(staker.acc_reward, 40);
+    }
+    #[test]
+    fn test_nft_rate_change() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(0))
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        let collection_id: AccountId = "nft.testnet".parse().unwrap();
+        contract.add_nft_collection(collection_id.clone(), U128(2));
+
+        testing_env!(context
+            .predecessor_account_id(collection_id.clone())
+            .build());
+        contract.nft_on_transfer(accounts(1), accounts(1), "1".to_string(), "".to_string());
+
+        //The new rate only applies from the update on, even for unsettled stakers
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .block_timestamp(10 * 60 * 1_000_000_000)
+            .build());
+        contract.update_nft_collection(collection_id.clone(), U128(10));
+        testing_env!(context.predecessor_account_id(collection_id).build());
+        contract.nft_on_transfer(accounts(2), accounts(2), "2".to_string(), "".to_string());
+
+        testing_env!(context.block_timestamp(15 * 60 * 1_000_000_000).build());
+        assert_eq!(
+            contract.get_nft_stake_by_id(accounts(1)).acc_reward,
+            20 + 50
+        );
+        assert_eq!(contract.get_nft_stake_by_id(accounts(2)).acc_reward, 50);
+    }
+    #[test]
+    #[should_panic(expected = "Token is not staked by this account")]
+    fn test_nft_unstake_not_owner() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(0))
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        let collection_id: AccountId = "nft.testnet".parse().unwrap();
+        contract.add_nft_collection(collection_id.clone(), U128(2));
+
+        testing_env!(context
+            .predecessor_account_id(collection_id.clone())
+            .build());
+        contract.nft_on_transfer(accounts(1), accounts(1), "1".to_string(), "".to_string());
+        contract.nft_on_transfer(accounts(2), accounts(2), "2".to_string(), "".to_string());
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.nft_unstake(collection_id, "2".to_string());
+    }
+    #[test]
+    fn test_reward_rate_change_is_checkpointed() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.ft_internal_mint(&accounts(1), 1000);
+
+        stake_neko(&mut contract, &mut context, 1000, None);
+
+        testing_env!(context
+            .signer_account_id(accounts(0))
+            .block_timestamp(10 * 60 * 1_000_000_000)
+            .build());
+        apply_change(&mut contract, ParamChange::RewardRate(10));
+
+        testing_env!(context.block_timestamp(20 * 60 * 1_000_000_000).build());
+        let stake_data = contract.get_stake_by_id(accounts(1));
+        assert_eq!(stake_data.total_stake, 1000);
+        assert_eq!(stake_data.acc_reward, 500 + 1000);
+        assert_eq!(contract.get_reward_index().total_shares, 1000);
+    }
+    #[test]
+    fn test_unbonding_withdraw() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.set_unbonding_period(100);
+        contract.ft_internal_mint(&accounts(1), 1000);
+
+        stake_neko(&mut contract, &mut context, 1000, None);
+        unstake_neko(&mut contract, &mut context, 400, balance_result(400));
+        assert_eq!(contract.get_stake_by_id(accounts(1)).total_stake, 600);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 0);
+        let entries = contract.get_unbonding(accounts(1));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].amount, 400);
+        assert_eq!(entries[0].release_time, 100);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .block_timestamp(100)
+            .build());
+        assert_eq!(contract.withdraw_unbonded().0, 400);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 400);
+        assert!(contract.get_unbonding(accounts(1)).is_empty());
+    }
+    #[test]
+    #[should_panic(expected = "No matured unbonding entries")]
+    fn test_unbonding_not_matured() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.set_unbonding_period(100);
+        contract.ft_internal_mint(&accounts(1), 1000);
+
+        stake_neko(&mut contract, &mut context, 1000, None);
+        unstake_neko(&mut contract, &mut context, 400, balance_result(400));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .block_timestamp(99)
+            .build());
+        contract.withdraw_unbonded();
+    }
+    #[test]
+    fn test_lock_position_early_exit() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.set_lock_tiers(vec![LockTier {
+            lock_days: 30,
+            multiplier_bps: 20_000,
+        }]);
+        apply_change(&mut contract, ParamChange::EarlyExitPenalty(Some(1_000)));
+        contract.ft_internal_mint(&accounts(1), 2000);
+
+        stake_neko(&mut contract, &mut context, 1000, None);
+        stake_neko(&mut contract, &mut context, 1000, Some(0));
+        let stake_data = contract.get_stake_by_id(accounts(1));
+        assert_eq!(stake_data.total_stake, 2000);
+        assert_eq!(stake_data.shares, 3000);
+        assert_eq!(stake_data.positions.len(), 1);
+
+        testing_env!(context.block_timestamp(10 * 60 * 1_000_000_000).build());
+        assert_eq!(contract.get_stake_by_id(accounts(1)).acc_reward, 1500);
+
+        //Only flexible stake can be unstaked without a position
+        unstake_neko(&mut contract, &mut context, 1000, balance_result(1000));
+        assert_eq!(contract.get_stake_by_id(accounts(1)).total_stake, 1000);
+
+        with_promise_result(context.build(), balance_result(1000));
+        contract.position_exchange_call_back(accounts(1), 0, 1000);
+        let stake_data = contract.get_stake_by_id(accounts(1));
+        assert_eq!(stake_data.total_stake, 0);
+        assert_eq!(stake_data.shares, 0);
+        assert_eq!(
+            contract.ft_balance_of("vault.testnet".parse().unwrap()).0,
+            100
+        );
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 1000 + 900);
+    }
+    #[test]
+    #[should_panic(expected = "Lock position is still locked")]
+    fn test_lock_position_locked() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.set_lock_tiers(vec![LockTier {
+            lock_days: 30,
+            multiplier_bps: 20_000,
+        }]);
+        contract.ft_internal_mint(&accounts(1), 1000);
+
+        stake_neko(&mut contract, &mut context, 1000, Some(0));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.unstake_position(0);
+    }
+    #[test]
+    fn test_lock_position_exit_in_progress() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.set_lock_tiers(vec![LockTier {
+            lock_days: 0,
+            multiplier_bps: 20_000,
+        }]);
+        contract.ft_internal_mint(&accounts(1), 1000);
+        stake_neko(&mut contract, &mut context, 1000, Some(0));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.unstake_position(0);
+        assert!(contract.get_stake_by_id(accounts(1)).positions[0].exiting);
+
+        //A failed exchange lets the position exit again
+        with_promise_result(
+            context.predecessor_account_id(accounts(0)).build(),
+            PromiseResult::Failed,
+        );
+        contract.position_exchange_call_back(accounts(1), 0, 1000);
+        assert!(!contract.get_stake_by_id(accounts(1)).positions[0].exiting);
+    }
+    #[test]
+    #[should_panic(expected = "Lock position is already exiting")]
+    fn test_lock_position_exit_twice() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.set_lock_tiers(vec![LockTier {
+            lock_days: 0,
+            multiplier_bps: 20_000,
+        }]);
+        contract.ft_internal_mint(&accounts(1), 1000);
+        stake_neko(&mut contract, &mut context, 1000, Some(0));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.unstake_position(0);
+        contract.unstake_position(0);
+    }
+    #[test]
+    #[should_panic(expected = "Insufficient unlocked stake")]
+    fn test_unstake_locked_stake() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.set_lock_tiers(vec![LockTier {
+            lock_days: 30,
+            multiplier_bps: 20_000,
+        }]);
+        contract.ft_internal_mint(&accounts(1), 2000);
+        stake_neko(&mut contract, &mut context, 1000, None);
+        stake_neko(&mut contract, &mut context, 1000, Some(0));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.unstake(1500);
+    }
+    #[test]
+    fn test_redemption_shortfall() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.ft_internal_mint(&accounts(1), 1000);
+        contract.ft_internal_mint(&accounts(2), 500);
+        stake_neko(&mut contract, &mut context, 1000, None);
+        assert_eq!(contract.get_reserve().reserve.0, 1000);
+
+        //Stake redeemed for more NEKO than the reserve holds is not minted
+        contract.staking_reserve = 600;
+        let total_supply = contract.ft_total_supply().0;
+        unstake_neko(&mut contract, &mut context, 1000, balance_result(1000));
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 600);
+        assert_eq!(contract.ft_total_supply().0, total_supply);
+        let reserve = contract.get_reserve();
+        assert_eq!(reserve.reserve.0, 0);
+        assert_eq!(reserve.total_staked.0, 0);
+        assert_eq!(reserve.total_shortfall.0, 400);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+        contract.fund_reserve(U128(300));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        assert_eq!(contract.claim_shortfall().0, 300);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 900);
+        assert_eq!(contract.get_reserve().total_shortfall.0, 100);
+    }
+    #[test]
+    fn test_emergency_withdraw() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.set_lock_tiers(vec![LockTier {
+            lock_days: 30,
+            multiplier_bps: 20_000,
+        }]);
+        contract.ft_internal_mint(&accounts(1), 2000);
+        stake_neko(&mut contract, &mut context, 1000, None);
+        stake_neko(&mut contract, &mut context, 1000, Some(0));
+
+        testing_env!(context.block_timestamp(10 * 60 * 1_000_000_000).build());
+        contract.set_emergency_mode(true);
+        assert!(contract.is_emergency_mode());
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        assert_eq!(contract.emergency_withdraw().0, 2000);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 2000);
+        assert!(contract.stake.get(&accounts(1)).is_none());
+        assert_eq!(contract.get_reward_index().total_shares, 0);
+        assert!(near_sdk::test_utils::get_logs()
+            .iter()
+            .any(|log| log.contains("emergency_withdraw") && log.contains("1500")));
+    }
+    #[test]
+    #[should_panic(expected = "Emergency mode is not active")]
+    fn test_emergency_withdraw_inactive() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.ft_internal_mint(&accounts(1), 1000);
+        stake_neko(&mut contract, &mut context, 1000, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.emergency_withdraw();
+    }
+    #[test]
+    fn test_emergency_withdraw_capped() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.ft_internal_mint(&accounts(1), 1000);
+        stake_neko(&mut contract, &mut context, 1000, None);
+        contract.set_emergency_mode(true);
+        contract.staking_reserve = 600;
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        assert_eq!(contract.emergency_withdraw().0, 600);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 600);
+        assert_eq!(contract.staking_reserve, 0);
+        assert_eq!(contract.shortfall.get(&accounts(1)), Some(400));
+        assert_eq!(contract.total_shortfall, 400);
+
+        //The exit doesn't depend on the factory
+        assert!(near_sdk::test_utils::get_created_receipts().is_empty());
+    }
+    #[test]
+    #[should_panic(expected = "Unstaking is closed in emergency mode, use emergency_withdraw")]
+    fn test_emergency_mode_blocks_unstake() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.ft_internal_mint(&accounts(1), 1000);
+        stake_neko(&mut contract, &mut context, 1000, None);
+        contract.set_emergency_mode(true);
+
+        unstake_neko(&mut contract, &mut context, 1000, balance_result(1000));
+    }
+    #[test]
+    #[should_panic(expected = "Claiming is closed in emergency mode")]
+    fn test_emergency_mode_blocks_claim() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.ft_internal_mint(&accounts(1), 1000);
+        stake_neko(&mut contract, &mut context, 1000, None);
+        contract.set_emergency_mode(true);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .block_timestamp(10 * 60 * 1_000_000_000)
+            .build());
+        contract.claim_cookie();
+    }
+    #[test]
+    fn test_compound() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.ft_internal_mint(&accounts(1), 1000);
+
+        stake_neko(&mut contract, &mut context, 1000, None);
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_auto_compound(true);
+
+        //Nothing to compound while the reserve has no surplus to back it
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .block_timestamp(10 * 60 * 1_000_000_000)
+            .build());
+        assert!(contract.internal_compound(&accounts(1)).is_none());
+
+        contract.ft_internal_mint(&accounts(2), 300);
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+        contract.fund_reserve(U128(300));
+
+        //Nothing compounds while claiming is paused
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.set_paused(Feature::Claiming, true);
+        assert!(contract.internal_compound(&accounts(1)).is_none());
+        contract.set_paused(Feature::Claiming, false);
+
+        //Only what the surplus backs is compounded, the rest of the reward is kept
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        assert_eq!(contract.compound(accounts(1)).0, 300);
+        let stake_data = contract.get_stake_by_id(accounts(1));
+        assert_eq!(stake_data.total_stake, 1300);
+        assert_eq!(stake_data.acc_reward, 200);
+        let reserve = contract.get_reserve();
+        assert_eq!(reserve.reserve.0, 1300);
+        assert_eq!(reserve.total_staked.0, 1300);
+        assert_eq!(contract.ft_balance_of(accounts(0)).0, 1300);
+        assert!(near_sdk::test_utils::get_logs()
+            .iter()
+            .any(|log| log.contains("\"event\":\"compound\"")));
+    }
+    #[test]
+    fn test_stake_reserve_matches_stake() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.ft_internal_mint(&accounts(1), 1000);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(2)
+            .build());
+        contract.stake(1000, None);
+        //The factory reports another amount than the NEKO held after the fee
+        with_promise_result(
+            context
+                .predecessor_account_id(accounts(0))
+                .attached_deposit(1)
+                .build(),
+            balance_result(900),
+        );
+        contract.neko_stake_call_back(accounts(1), 1000, 50, None);
+        let reserve = contract.get_reserve();
+        assert_eq!(reserve.reserve.0, 950);
+        assert_eq!(reserve.total_staked.0, 950);
+        assert_eq!(contract.get_stake_by_id(accounts(1)).total_stake, 950);
+    }
+    #[test]
+    fn test_stake_events() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.ft_internal_mint(&accounts(1), 1000);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(2)
+            .build());
+        contract.stake(1000, None);
+        with_promise_result(
+            context
+                .predecessor_account_id(accounts(0))
+                .attached_deposit(1)
+                .build(),
+            balance_result(950),
+        );
+        contract.neko_stake_call_back(accounts(1), 1000, 50, None);
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs.contains(&r#"EVENT_JSON:{"standard":"neko_stake","version":"1.0.0","event":"fee_charged","data":[{"account_id":"bob","action":"stake","amount":"50"}]}"#.to_string()));
+        assert!(logs.contains(&r#"EVENT_JSON:{"standard":"neko_stake","version":"1.0.0","event":"stake","data":[{"account_id":"bob","amount":"950"}]}"#.to_string()));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .block_timestamp(10 * 60 * 1_000_000_000)
+            .build());
+        contract.claim_cookie();
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs.contains(&r#"EVENT_JSON:{"standard":"neko_stake","version":"1.0.0","event":"claim","data":[{"account_id":"bob","amount":"452"}]}"#.to_string()));
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        apply_change(&mut contract, ParamChange::RewardRate(3));
+        assert!(near_sdk::test_utils::get_logs()
+            .iter()
+            .any(|log| log.contains("rate_changed") && log.contains("\"reward_rate\":3")));
+    }
+    #[test]
+    fn test_stake_callback_failures() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.ft_internal_mint(&accounts(1), 2000);
+
+        //A failed bake refunds the NEKO held for the stake
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(2)
+            .build());
+        contract.stake(1000, None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 1000);
+        with_promise_result(
+            context
+                .predecessor_account_id(accounts(0))
+                .attached_deposit(1)
+                .build(),
+            PromiseResult::Failed,
+        );
+        contract.neko_stake_call_back(accounts(1), 1000, 50, None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 2000);
+        assert!(contract.stake.get(&accounts(1)).is_none());
+        assert!(near_sdk::test_utils::get_logs()
+            .iter()
+            .any(|log| log.contains("stake_failed")));
+
+        //A failed reward transfer gives the reward back
+        stake_neko(&mut contract, &mut context, 1000, None);
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .block_timestamp(10 * 60 * 1_000_000_000)
+            .build());
+        contract.claim_cookie();
+        assert_eq!(contract.get_stake_by_id(accounts(1)).acc_reward, 0);
+        with_promise_result(
+            context.predecessor_account_id(accounts(0)).build(),
+            PromiseResult::Failed,
+        );
+        contract.claim_reward_call_back(accounts(1), 500);
+        assert_eq!(contract.get_stake_by_id(accounts(1)).acc_reward, 500);
+
+        //A failed exchange leaves the stake untouched
+        unstake_neko(&mut contract, &mut context, 400, PromiseResult::Failed);
+        assert_eq!(contract.get_stake_by_id(accounts(1)).total_stake, 1000);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 1000);
+    }
+    #[test]
+    fn test_emission_schedule_epochs_and_halving() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        let minute = 60 * 1_000_000_000;
+        apply_change(
+            &mut contract,
+            ParamChange::EmissionSchedule {
+                epochs: vec![
+                    Epoch {
+                        start_time: 0,
+                        reward_rate: 5,
+                    },
+                    Epoch {
+                        start_time: 10 * minute,
+                        reward_rate: 8,
+                    },
+                ],
+                halving_interval: 10 * minute,
+                max_emission: None,
+            },
+        );
+        contract.ft_internal_mint(&accounts(1), 1000);
+
+        stake_neko(&mut contract, &mut context, 1000, None);
+
+        //10 minutes at 5%, then 8% halving to 4% after another 10 minutes
+        testing_env!(context.block_timestamp(30 * minute).build());
+        assert_eq!(contract.get_reward_rate(), 2);
+        assert_eq!(
+            contract.get_stake_by_id(accounts(1)).acc_reward,
+            500 + 800 + 400
+        );
+    }
+    #[test]
+    fn test_emission_schedule_cap() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .block_timestamp(0)
+            .build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        apply_change(
+            &mut contract,
+            ParamChange::EmissionSchedule {
+                epochs: vec![Epoch {
+                    start_time: 0,
+                    reward_rate: 5,
+                }],
+                halving_interval: 0,
+                max_emission: Some(U128(700)),
+            },
+        );
+        contract.ft_internal_mint(&accounts(1), 1000);
+
+        stake_neko(&mut contract, &mut context, 1000, None);
+
+        testing_env!(context.block_timestamp(10 * 60 * 1_000_000_000).build());
+        contract.update_stake_data(&accounts(1));
+        assert_eq!(contract.get_stake_by_id(accounts(1)).acc_reward, 500);
+
+        testing_env!(context.block_timestamp(20 * 60 * 1_000_000_000).build());
+        contract.update_stake_data(&accounts(1));
+        assert_eq!(contract.get_stake_by_id(accounts(1)).acc_reward, 700);
+        assert_eq!(contract.get_emission_schedule().total_emitted, 700);
+    }
+
+    // layout of the baseline release, written without a state version
+    #[derive(BorshSerialize)]
+    struct BaselineToken {
+        accounts: LookupMap<AccountId, Balance>,
+        total_supply: Balance,
+        account_storage_usage: u64,
+        vault: AccountId,
+    }
+    #[derive(BorshSerialize)]
+    struct BaselineState {
+        token: BaselineToken,
+        metadata: LazyOption<FungibleTokenMetadata>,
+        owner_id: AccountId,
+        stake: UnorderedMap<AccountId, StakeV1>,
+        factory_id: AccountId,
+        vault_id: AccountId,
+        fee_percent: u8,
+        cookie_reward_rate: u8,
+    }
+
+    #[test]
+    fn test_migrate_from_baseline() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut token = BaselineToken {
+            accounts: LookupMap::new(StorageKey::FungibleToken),
+            total_supply: 1050,
+            account_storage_usage: 0,
+            vault: "vault.testnet".parse().unwrap(),
+        };
+        token.accounts.insert(&accounts(0), &1000);
+        token.accounts.insert(&accounts(1), &50);
+        let mut stake = UnorderedMap::new(StorageKey::StakeData);
+        stake.insert(
+            &accounts(1),
+            &StakeV1 {
+                total_stake: 1000,
+                acc_reward: 20,
+                last_update_time: 0,
+            },
+        );
+        env::state_write(&BaselineState {
+            token,
+            metadata: LazyOption::new(StorageKey::Metadata, None),
+            owner_id: accounts(0),
+            stake,
+            factory_id: "factory.testnet".parse().unwrap(),
+            vault_id: "vault.testnet".parse().unwrap(),
+            fee_percent: 5,
+            cookie_reward_rate: 5,
+        });
+
+        testing_env!(context.block_timestamp(10 * 60 * 1_000_000_000).build());
+        let mut contract = Contract::migrate();
+        assert_eq!(contract.contract_version().state_version, 1);
+        assert_eq!(contract.get_owner(), Some(accounts(0)));
+        assert_eq!(contract.get_config().bake_fee_bps, 500);
+        assert_eq!(contract.ft_total_supply(), U128(1050));
+        assert_eq!(contract.ft_balance_of(accounts(1)), U128(50));
+        assert_eq!(contract.staking_reserve, 1000);
+        //the legacy stake counts towards the emission before its record is touched
+        assert_eq!(contract.get_reward_index().total_shares, 1000);
+        assert_eq!(contract.total_staked, 1000);
+        //the legacy reward is settled up to the migration
+        assert_eq!(contract.get_stake_by_id(accounts(1)).acc_reward, 520);
+
+        //accounts registered before the migration have to be indexed for the audit
+        assert_ne!(contract.ft_audit_supply(0, 10).difference.0, 0);
+        assert_eq!(
+            contract.ft_index_accounts(vec![accounts(0), accounts(1), accounts(2)]),
+            2
+        );
+        assert_eq!(contract.ft_index_accounts(vec![accounts(1)]), 0);
+        let report = contract.ft_audit_supply(0, 10);
+        assert!(report.complete);
+        assert_eq!(report.difference.0, 0);
+
+        testing_env!(context.block_timestamp(20 * 60 * 1_000_000_000).build());
+        contract.update_stake_data(&accounts(1));
+        assert!(contract.legacy_stakes.records.is_empty());
+        assert_eq!(contract.total_staked, 1000);
+        assert_eq!(contract.get_reward_index().total_shares, 1000);
+        assert_eq!(contract.get_stake_by_id(accounts(1)).acc_reward, 1020);
+        assert_eq!(contract.get_emission_schedule().total_emitted, 500);
+    }
+
+    #[test]
+    fn test_migrate_up_to_date() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.ft_internal_mint(&accounts(1), 1000);
+        env::state_write(&contract);
+
+        let contract = Contract::migrate();
+        assert_eq!(contract.ft_balance_of(accounts(1)), U128(1000));
+        assert_eq!(contract.get_config().bake_fee_bps, 500);
+    }
+
+    #[test]
+    fn test_stage_and_deploy_code() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        let deploy_after = STAGING_DURATION + 1;
+        contract.stage_code(vec![1, 2, 3].into(), deploy_after);
+        let staged = contract.get_staged_code().unwrap();
+        assert_eq!(staged.sha256, env::sha256(&[1, 2, 3]).into());
+        assert_eq!(staged.deploy_after, deploy_after);
+
+        //anyone can deploy once staging ended
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .block_timestamp(deploy_after)
+            .build());
+        contract.deploy_code();
+        assert!(contract.get_staged_code().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Deploy code too early")]
+    fn test_deploy_code_too_early() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
+            5,
+        );
+        contract.stage_code(vec![1, 2, 3].into(), STAGING_DURATION + 1);
+
+        testing_env!(context.block_timestamp(STAGING_DURATION).build());
+        contract.deploy_code();
+    }
+
+    #[test]
+    #[should_panic(expected = "No upgrade code available")]
+    fn test_cancel_staged_code() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(
+            accounts(0),
+            "vault.testnet".parse().unwrap(),
+            "factory.testnet".parse().unwrap(),
+            500,
             5,
         );
+        contract.stage_code(vec![1, 2, 3].into(), STAGING_DURATION + 1);
+        contract.cancel_staged_code();
+        assert!(contract.get_staged_code().is_none());
 
-        contract.set_reward_rate(10);
+        testing_env!(context.block_timestamp(STAGING_DURATION + 1).build());
+        contract.deploy_code();
     }
 }
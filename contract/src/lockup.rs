@@ -0,0 +1,199 @@
+use super::*;
+use crate::stake::promise_result_balance;
+use crate::stake_events::{FeeCharged, StakeFailed, Unstake};
+use near_sdk::{assert_one_yocto, ext_contract, Promise, ONE_YOCTO};
+use serde::{Deserialize, Serialize};
+
+/// Basis of `multiplier_bps` and `early_exit_penalty_bps`, 10000 is 1x / 100%.
+pub const MULTIPLIER_BASE: u128 = 10_000;
+const NANOS_PER_DAY: u64 = 1_000_000_000 * 60 * 60 * 24;
+
+#[ext_contract(ext_factory_contract)]
+pub trait Factory {
+    fn checked_exchange(&mut self, amount: Balance) -> Balance;
+}
+#[ext_contract(ext_self)]
+pub trait LockupCallBack {
+    fn position_exchange_call_back(
+        &mut self,
+        account_id: AccountId,
+        position_id: u64,
+        amount: Balance,
+    );
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LockTier {
+    pub lock_days: u32,
+    pub multiplier_bps: u32,
+}
+
+/// Stake locked until `unlock_time`, earning rewards at `multiplier_bps`.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Debug, Clone)]
+pub struct LockPosition {
+    pub id: u64,
+    pub amount: Balance,
+    pub multiplier_bps: u32,
+    pub unlock_time: u64,
+    /// Set while the cookies of the position are being exchanged by `unstake_position`.
+    pub exiting: bool,
+}
+impl LockPosition {
+    pub fn new(id: u64, amount: Balance, tier: &LockTier) -> Self {
+        Self {
+            id,
+            amount,
+            multiplier_bps: tier.multiplier_bps,
+            unlock_time: env::block_timestamp() + tier.lock_days as u64 * NANOS_PER_DAY,
+            exiting: false,
+        }
+    }
+    /// Stake weighted by the lock multiplier.
+    pub fn shares(&self) -> Balance {
+        self.amount * self.multiplier_bps as u128 / MULTIPLIER_BASE
+    }
+    pub fn is_locked(&self) -> bool {
+        env::block_timestamp() < self.unlock_time
+    }
+}
+
+//Call Methods
+#[near_bindgen]
+impl Contract {
+    /// Unstakes a whole lock position. Before `unlock_time` this is only
+    /// possible when an early exit penalty is configured, the penalty is sent
+    /// to the vault.
+    #[payable]
+    pub fn unstake_position(&mut self, position_id: u64) -> Promise {
+        assert_one_yocto();
+        self.assert_not_paused(Feature::Unstaking);
+        require!(
+            !self.emergency_mode,
+            "Unstaking is closed in emergency mode, use emergency_withdraw"
+        );
+        let account_id = env::predecessor_account_id();
+        self.internal_upgrade_stake(&account_id);
+        let mut stake_data = self
+            .stake
+            .get(&account_id)
+            .unwrap_or_else(|| panic!("Lock position not found"));
+        let position = stake_data
+            .positions
+            .iter_mut()
+            .find(|position| position.id == position_id)
+            .unwrap_or_else(|| panic!("Lock position not found"));
+        require!(!position.exiting, "Lock position is already exiting");
+        if position.is_locked() {
+            require!(
+                self.config.early_exit_penalty_bps.is_some(),
+                "Lock position is still locked"
+            );
+        }
+        position.exiting = true;
+        let position = position.clone();
+        self.stake.insert(&account_id, &stake_data);
+
+        ext_factory_contract::checked_exchange(
+            position.amount,
+            self.config.factory_id.clone(),
+            ONE_YOCTO,
+            env::prepaid_gas() / 3,
+        )
+        .then(ext_self::position_exchange_call_back(
+            account_id,
+            position_id,
+            position.amount,
+            env::current_account_id(),
+            ONE_YOCTO,
+            env::prepaid_gas() / 3,
+        ))
+    }
+    #[private]
+    #[payable]
+    pub fn position_exchange_call_back(
+        &mut self,
+        account_id: AccountId,
+        position_id: u64,
+        amount: Balance,
+    ) {
+        let mut stake_data = self.stake.get(&account_id).unwrap_or_default();
+        if promise_result_balance().is_none() {
+            //Exchange failed, the position stays locked in
+            if let Some(position) = stake_data
+                .positions
+                .iter_mut()
+                .find(|position| position.id == position_id)
+            {
+                position.exiting = false;
+                self.stake.insert(&account_id, &stake_data);
+            }
+            StakeFailed {
+                account_id: &account_id,
+                action: "unstake_position",
+                amount: &U128(amount),
+                memo: None,
+            }
+            .emit();
+            return;
+        }
+        self.checkpoint_reward();
+        let position = match stake_data.unlock(position_id, &mut self.reward_index) {
+            Some(position) => position,
+            None => {
+                //Exiting positions can't be removed, nothing is paid out of inconsistent state
+                StakeFailed {
+                    account_id: &account_id,
+                    action: "unstake_position",
+                    amount: &U128(amount),
+                    memo: Some("Lock position not found"),
+                }
+                .emit();
+                return;
+            }
+        };
+        self.stake.insert(&account_id, &stake_data);
+        self.total_staked -= position.amount;
+        Unstake {
+            account_id: &account_id,
+            amount: &U128(position.amount),
+            position_id: Some(position_id),
+        }
+        .emit();
+
+        let penalty = match self.config.early_exit_penalty_bps {
+            Some(penalty_bps) if position.is_locked() => {
+                position.amount * penalty_bps as u128 / MULTIPLIER_BASE
+            }
+            _ => 0,
+        };
+        self.internal_redeem(&account_id, position.amount - penalty);
+        //The penalty is only paid out of what is left in the reserve
+        let penalty = std::cmp::min(penalty, self.staking_reserve);
+        if penalty > 0 {
+            self.staking_reserve -= penalty;
+            let vault = self.token.vault.clone();
+            self.token.internal_transfer(
+                &env::current_account_id(),
+                &vault,
+                penalty,
+                Some("Early exit penalty".to_string()),
+            );
+            FeeCharged {
+                account_id: &account_id,
+                action: "early_exit",
+                amount: &U128(penalty),
+            }
+            .emit();
+        }
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_get_lock_tier(&self, tier: u8) -> LockTier {
+        self.config
+            .lock_tiers
+            .get(tier as usize)
+            .cloned()
+            .unwrap_or_else(|| panic!("Lock tier not found"))
+    }
+}
@@ -0,0 +1,99 @@
+use super::*;
+use near_sdk::borsh::BorshSerialize;
+use serde::Serialize;
+
+#[derive(Serialize, Debug)]
+pub struct MaintenanceReport {
+    pub keys_removed: u64,
+    /// Bytes of storage released by the call.
+    pub storage_freed: u64,
+}
+
+//Call Methods
+#[near_bindgen]
+impl Contract {
+    /// Maintenance can only start while every feature is paused.
+    pub fn set_maintenance_mode(&mut self, enabled: bool) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_set_maintenance_mode(enabled);
+    }
+    /// Removes up to `MAX_MAINTENANCE_BATCH` raw `keys` of the collection stored under
+    /// `prefix`. Collections in use by the contract are only touched in maintenance mode,
+    /// the ones it left behind can be cleared at any time.
+    pub fn clear_storage(
+        &mut self,
+        prefix: StorageKey,
+        keys: Vec<Base64VecU8>,
+    ) -> MaintenanceReport {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_clear_storage(prefix, keys)
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_set_maintenance_mode(&mut self, enabled: bool) {
+        if enabled {
+            require!(
+                [
+                    Feature::Transfers,
+                    Feature::Staking,
+                    Feature::Claiming,
+                    Feature::Unstaking,
+                    Feature::Minting,
+                ]
+                .iter()
+                .all(|feature| self.paused.is_paused(*feature)),
+                "Pause every feature before entering maintenance"
+            );
+        }
+        self.maintenance_mode = enabled;
+        log!("Maintenance mode set to {}", enabled);
+    }
+    pub(crate) fn internal_clear_storage(
+        &mut self,
+        prefix: StorageKey,
+        keys: Vec<Base64VecU8>,
+    ) -> MaintenanceReport {
+        require!(
+            keys.len() <= MAX_MAINTENANCE_BATCH,
+            "Too many keys for one batch"
+        );
+        require!(
+            self.maintenance_mode || !self.is_live_prefix(&prefix),
+            "Collection is live, enter maintenance mode first"
+        );
+        let prefix = prefix.try_to_vec().unwrap();
+        let storage_before = env::storage_usage();
+        let mut keys_removed = 0;
+        for key in keys.iter() {
+            require!(
+                key.0.starts_with(&prefix),
+                "Key is outside of the storage prefix"
+            );
+            if env::storage_remove(&key.0) {
+                keys_removed += 1;
+            }
+        }
+        let report = MaintenanceReport {
+            keys_removed,
+            storage_freed: storage_before - env::storage_usage(),
+        };
+        log!(
+            "Removed {} keys, freed {} bytes",
+            report.keys_removed,
+            report.storage_freed
+        );
+        report
+    }
+    /// The stake collections of removed pools and the legacy stake records once every
+    /// record was upgraded are the only ones left behind by the contract.
+    fn is_live_prefix(&self, prefix: &StorageKey) -> bool {
+        match prefix {
+            StorageKey::PoolStake { token_id } => self.pools.get(token_id).is_some(),
+            StorageKey::StakeData => !self.legacy_stakes.records.is_empty(),
+            _ => true,
+        }
+    }
+}
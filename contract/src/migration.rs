@@ -0,0 +1,169 @@
+use super::*;
+use crate::config::BPS_DENOMINATOR;
+use crate::stake::StakeV1;
+use near_contract_standards::fungible_token::FungibleTokenV0;
+use serde::Serialize;
+
+/// Version of the state layout, stored next to the state since the baseline
+/// release didn't store one.
+pub const STATE_VERSION: u32 = 1;
+const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+
+/// State layout of the baseline release.
+#[derive(BorshDeserialize)]
+pub struct ContractV0 {
+    token: FungibleTokenV0,
+    metadata: LazyOption<FungibleTokenMetadata>,
+    owner_id: AccountId,
+    stake: UnorderedMap<AccountId, StakeV1>,
+    factory_id: AccountId,
+    vault_id: AccountId,
+    fee_percent: u8,
+    cookie_reward_rate: u8,
+}
+
+/// Contract state in the layout it was written in.
+pub enum VersionedState {
+    V0(Box<ContractV0>),
+    V1(Box<Contract>),
+}
+impl VersionedState {
+    pub fn read() -> Self {
+        let version = env::storage_read(STATE_VERSION_KEY)
+            .map(|bytes| u32::try_from_slice(&bytes).unwrap_or_else(|_| env::abort()))
+            .unwrap_or(0);
+        match version {
+            0 => Self::V0(Box::new(
+                env::state_read().unwrap_or_else(|| panic!("No state to migrate")),
+            )),
+            1 => Self::V1(Box::new(
+                env::state_read().unwrap_or_else(|| panic!("No state to migrate")),
+            )),
+            _ => panic!("Unknown state version {}", version),
+        }
+    }
+    pub fn version(&self) -> u32 {
+        match self {
+            Self::V0(_) => 0,
+            Self::V1(_) => 1,
+        }
+    }
+    pub fn into_current(self) -> Contract {
+        match self {
+            Self::V0(old) => Contract::from_v0(*old),
+            Self::V1(contract) => *contract,
+        }
+    }
+}
+
+/// Stake records still in the [`StakeV1`] layout, each is upgraded the first
+/// time its account uses the stake.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct LegacyStakes {
+    pub records: UnorderedMap<AccountId, StakeV1>,
+    /// Rate the records earned at until `migrated_at`, after that they earn
+    /// through the reward index.
+    pub reward_rate: u8,
+    pub migrated_at: u64,
+}
+impl LegacyStakes {
+    pub fn new() -> Self {
+        Self::from_records(UnorderedMap::new(StorageKey::StakeData), 0)
+    }
+    fn from_records(records: UnorderedMap<AccountId, StakeV1>, reward_rate: u8) -> Self {
+        Self {
+            records,
+            reward_rate,
+            migrated_at: env::block_timestamp(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ContractVersion {
+    pub version: String,
+    pub state_version: u32,
+}
+
+//Call Methods
+#[near_bindgen]
+impl Contract {
+    /// Converts state written by an older release to the current layout,
+    /// called by `deploy_code` so it keeps up to date state as it is.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let state = VersionedState::read();
+        if state.version() == STATE_VERSION {
+            log!("State is already up to date");
+        }
+        let contract = state.into_current();
+        Self::write_state_version();
+        contract
+    }
+    pub fn contract_version(&self) -> ContractVersion {
+        ContractVersion {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            state_version: STATE_VERSION,
+        }
+    }
+}
+
+impl Contract {
+    pub(crate) fn write_state_version() {
+        env::storage_write(STATE_VERSION_KEY, &STATE_VERSION.try_to_vec().unwrap());
+    }
+    /// The baseline held the staked NEKO in this contract, it becomes the staking reserve.
+    fn from_v0(old: ContractV0) -> Self {
+        let bake_fee_bps = std::cmp::min(old.fee_percent as u16 * 100, BPS_DENOMINATOR);
+        let mut config = Config::new(old.factory_id, old.vault_id, 0);
+        config.max_bake_fee_bps = std::cmp::max(config.max_bake_fee_bps, bake_fee_bps);
+        config.max_reward_rate = std::cmp::max(config.max_reward_rate, old.cookie_reward_rate);
+        config.bake_fee_bps = bake_fee_bps;
+
+        let mut this = Self::internal_new(
+            old.owner_id,
+            old.token.migrate(StorageKey::FungibleToken),
+            old.metadata,
+            config,
+            EmissionSchedule::new(old.cookie_reward_rate),
+        );
+        this.legacy_stakes = LegacyStakes::from_records(old.stake, old.cookie_reward_rate);
+        //Legacy stakes earn through the index from now on, so their shares count right away
+        let legacy_stake: Balance = this
+            .legacy_stakes
+            .records
+            .values()
+            .map(|legacy| legacy.total_stake)
+            .sum();
+        this.reward_index.total_shares = legacy_stake;
+        this.total_staked = legacy_stake;
+        this.staking_reserve = this
+            .token
+            .accounts
+            .get(&env::current_account_id())
+            .unwrap_or(0);
+        this
+    }
+    /// Moves the stake of `account_id` out of the legacy records, if it is still there.
+    pub(crate) fn internal_upgrade_stake(&mut self, account_id: &AccountId) {
+        if let Some(legacy) = self.legacy_stakes.records.remove(account_id) {
+            let stake_data = legacy.upgrade(
+                self.legacy_stakes.reward_rate,
+                self.legacy_stakes.migrated_at,
+            );
+            self.stake.insert(account_id, &stake_data);
+        }
+    }
+    /// Stake of `account_id` for views, legacy records are upgraded in memory only.
+    pub(crate) fn internal_stake_of(&self, account_id: &AccountId) -> Option<StakeV2> {
+        self.stake.get(account_id).or_else(|| {
+            self.legacy_stakes.records.get(account_id).map(|legacy| {
+                legacy.upgrade(
+                    self.legacy_stakes.reward_rate,
+                    self.legacy_stakes.migrated_at,
+                )
+            })
+        })
+    }
+}
@@ -0,0 +1,355 @@
+use super::*;
+use crate::events::MultisigProposal;
+use crate::timelock::ParamChange;
+use near_sdk::collections::UnorderedSet;
+use serde::{Deserialize, Serialize};
+
+/// Admin action that runs once enough signers confirmed its proposal.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MultisigAction {
+    QueueChange {
+        change: ParamChange,
+        execute_after: u64,
+    },
+    ExecuteChange {
+        id: u64,
+    },
+    CancelChange {
+        id: u64,
+    },
+    Mint {
+        account_id: AccountId,
+        amount: U128,
+    },
+    SetMaintenanceMode {
+        enabled: bool,
+    },
+    StageCode {
+        code: Base64VecU8,
+        timestamp: u64,
+    },
+    CancelStagedCode,
+    ClearStorage {
+        prefix: StorageKey,
+        keys: Vec<Base64VecU8>,
+    },
+    SetTaxExemptSender {
+        account_id: AccountId,
+        exempt: bool,
+    },
+    SetTaxExemptReceiver {
+        account_id: AccountId,
+        exempt: bool,
+    },
+    SetCustomTaxRate {
+        account_id: AccountId,
+        rate_bps: Option<u16>,
+    },
+    SetLockTiers {
+        tiers: Vec<LockTier>,
+    },
+    SetUnbondingPeriod {
+        period: u64,
+    },
+    AddPool {
+        token_id: AccountId,
+        reward_rate: u8,
+        fee_percent: u8,
+    },
+    UpdatePool {
+        token_id: AccountId,
+        reward_rate: u8,
+        fee_percent: u8,
+    },
+    RemovePool {
+        token_id: AccountId,
+    },
+    AddNftCollection {
+        collection_id: AccountId,
+        reward_rate: U128,
+    },
+    UpdateNftCollection {
+        collection_id: AccountId,
+        reward_rate: U128,
+    },
+    RemoveNftCollection {
+        collection_id: AccountId,
+    },
+    SetEmergencyMode {
+        enabled: bool,
+    },
+    ProposeOwner {
+        owner_id: AccountId,
+    },
+    CancelOwnerProposal,
+    RenounceOwnership,
+    GrantRole {
+        account_id: AccountId,
+        role: Role,
+    },
+    RevokeRole {
+        account_id: AccountId,
+        role: Role,
+    },
+    SetMinterAllowance {
+        account_id: AccountId,
+        allowance: U128,
+    },
+    AddSigner {
+        account_id: AccountId,
+    },
+    RemoveSigner {
+        account_id: AccountId,
+    },
+    SetThreshold {
+        threshold: u32,
+    },
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Debug, Clone)]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: AccountId,
+    pub action: MultisigAction,
+    pub confirmations: Vec<AccountId>,
+    pub created_at: u64,
+}
+
+/// M-of-N signers that approve admin actions once it is set up.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Multisig {
+    pub signers: UnorderedSet<AccountId>,
+    pub threshold: u32,
+    pub proposals: UnorderedMap<u64, Proposal>,
+    pub next_proposal_id: u64,
+}
+impl Multisig {
+    pub fn new() -> Self {
+        Self {
+            signers: UnorderedSet::new(StorageKey::MultisigSigners),
+            threshold: 0,
+            proposals: UnorderedMap::new(StorageKey::MultisigProposals),
+            next_proposal_id: 0,
+        }
+    }
+    pub fn is_active(&self) -> bool {
+        !self.signers.is_empty()
+    }
+    fn is_expired(proposal: &Proposal) -> bool {
+        env::block_timestamp() >= proposal.created_at + MULTISIG_PROPOSAL_LIFETIME
+    }
+    /// Confirmations of accounts that are still signers.
+    fn confirmations(&self, proposal: &Proposal) -> u32 {
+        proposal
+            .confirmations
+            .iter()
+            .filter(|account_id| self.signers.contains(account_id))
+            .count() as u32
+    }
+}
+
+//Call Methods
+#[near_bindgen]
+impl Contract {
+    /// Hands admin actions over to `signers`, once set up only proposals can change them.
+    pub fn setup_multisig(&mut self, signers: Vec<AccountId>, threshold: u32) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        for account_id in signers.iter() {
+            self.multisig.signers.insert(account_id);
+        }
+        self.internal_set_threshold(threshold);
+    }
+    /// Proposes `action` and confirms it for the caller. Returns the proposal id.
+    pub fn propose(&mut self, action: MultisigAction) -> u64 {
+        let account_id = self.assert_signer();
+        let proposal = Proposal {
+            id: self.multisig.next_proposal_id,
+            proposer: account_id.clone(),
+            action,
+            confirmations: vec![],
+            created_at: env::block_timestamp(),
+        };
+        self.multisig.next_proposal_id += 1;
+        self.multisig.proposals.insert(&proposal.id, &proposal);
+        MultisigProposal {
+            proposal_id: proposal.id,
+            account_id: &account_id,
+            action: Some(&proposal.action),
+        }
+        .emit_created();
+        self.confirm(proposal.id);
+        proposal.id
+    }
+    /// Confirms proposal `id` for the caller and executes it once the threshold is reached.
+    pub fn confirm(&mut self, id: u64) {
+        let account_id = self.assert_signer();
+        let mut proposal = self
+            .multisig
+            .proposals
+            .get(&id)
+            .unwrap_or_else(|| panic!("No proposal found for this id"));
+        require!(!Multisig::is_expired(&proposal), "Proposal has expired");
+        require!(
+            !proposal.confirmations.contains(&account_id),
+            "Proposal already confirmed by this signer"
+        );
+        proposal.confirmations.push(account_id.clone());
+        MultisigProposal {
+            proposal_id: id,
+            account_id: &account_id,
+            action: None,
+        }
+        .emit_confirmed();
+
+        if self.multisig.confirmations(&proposal) < self.multisig.threshold {
+            self.multisig.proposals.insert(&id, &proposal);
+            return;
+        }
+        self.multisig.proposals.remove(&id);
+        self.internal_execute_action(proposal.action);
+        MultisigProposal {
+            proposal_id: id,
+            account_id: &account_id,
+            action: None,
+        }
+        .emit_executed();
+    }
+    /// Removes up to `limit` expired proposals, returns the number removed.
+    pub fn remove_expired_proposals(&mut self, limit: u64) -> u64 {
+        let expired: Vec<u64> = self
+            .multisig
+            .proposals
+            .values()
+            .filter(Multisig::is_expired)
+            .take(limit as usize)
+            .map(|proposal| proposal.id)
+            .collect();
+        let account_id = env::predecessor_account_id();
+        for id in expired.iter() {
+            self.multisig.proposals.remove(id);
+            MultisigProposal {
+                proposal_id: *id,
+                account_id: &account_id,
+                action: None,
+            }
+            .emit_expired();
+        }
+        expired.len() as u64
+    }
+}
+
+impl Contract {
+    /// Single key admin methods are closed once the multisig is set up.
+    pub(crate) fn assert_no_multisig(&self) {
+        require!(
+            !self.multisig.is_active(),
+            "Admin action requires a multisig proposal"
+        );
+    }
+    fn assert_signer(&self) -> AccountId {
+        let account_id = env::predecessor_account_id();
+        require!(
+            self.multisig.signers.contains(&account_id),
+            "Only multisig signers can do this"
+        );
+        account_id
+    }
+    fn internal_set_threshold(&mut self, threshold: u32) {
+        require!(
+            threshold > 0 && threshold as u64 <= self.multisig.signers.len(),
+            "Threshold must be between 1 and the number of signers"
+        );
+        self.multisig.threshold = threshold;
+    }
+    fn internal_execute_action(&mut self, action: MultisigAction) {
+        match action {
+            MultisigAction::QueueChange {
+                change,
+                execute_after,
+            } => {
+                self.internal_queue_change(change, execute_after);
+            }
+            MultisigAction::ExecuteChange { id } => self.internal_execute_change(id),
+            MultisigAction::CancelChange { id } => self.internal_cancel_change(id),
+            MultisigAction::Mint { account_id, amount } => {
+                self.assert_not_paused(Feature::Minting);
+                self.ft_internal_mint(&account_id, amount.0);
+            }
+            MultisigAction::SetMaintenanceMode { enabled } => {
+                self.internal_set_maintenance_mode(enabled)
+            }
+            MultisigAction::StageCode { code, timestamp } => {
+                self.upgrade.internal_stage_code(code.into(), timestamp)
+            }
+            MultisigAction::CancelStagedCode => self.upgrade.internal_cancel_code(),
+            MultisigAction::ClearStorage { prefix, keys } => {
+                self.internal_clear_storage(prefix, keys);
+            }
+            MultisigAction::SetTaxExemptSender { account_id, exempt } => {
+                self.internal_set_tax_exempt_sender(account_id, exempt)
+            }
+            MultisigAction::SetTaxExemptReceiver { account_id, exempt } => {
+                self.internal_set_tax_exempt_receiver(account_id, exempt)
+            }
+            MultisigAction::SetCustomTaxRate {
+                account_id,
+                rate_bps,
+            } => self.internal_set_custom_tax_rate(account_id, rate_bps),
+            MultisigAction::SetLockTiers { tiers } => self.internal_set_lock_tiers(tiers),
+            MultisigAction::SetUnbondingPeriod { period } => {
+                self.internal_set_unbonding_period(period)
+            }
+            MultisigAction::AddPool {
+                token_id,
+                reward_rate,
+                fee_percent,
+            } => self.internal_add_pool(token_id, reward_rate, fee_percent),
+            MultisigAction::UpdatePool {
+                token_id,
+                reward_rate,
+                fee_percent,
+            } => self.internal_update_pool(token_id, reward_rate, fee_percent),
+            MultisigAction::RemovePool { token_id } => self.internal_remove_pool(token_id),
+            MultisigAction::AddNftCollection {
+                collection_id,
+                reward_rate,
+            } => self.internal_add_nft_collection(collection_id, reward_rate),
+            MultisigAction::UpdateNftCollection {
+                collection_id,
+                reward_rate,
+            } => self.internal_update_nft_collection(collection_id, reward_rate),
+            MultisigAction::RemoveNftCollection { collection_id } => {
+                self.internal_remove_nft_collection(collection_id)
+            }
+            MultisigAction::SetEmergencyMode { enabled } => {
+                self.internal_set_emergency_mode(enabled)
+            }
+            MultisigAction::ProposeOwner { owner_id } => self.internal_propose_owner(owner_id),
+            MultisigAction::CancelOwnerProposal => self.internal_cancel_owner_proposal(),
+            MultisigAction::RenounceOwnership => self.internal_renounce_ownership(),
+            MultisigAction::GrantRole { account_id, role } => {
+                self.internal_admin_grant_role(account_id, role)
+            }
+            MultisigAction::RevokeRole { account_id, role } => {
+                self.internal_admin_revoke_role(account_id, role)
+            }
+            MultisigAction::SetMinterAllowance {
+                account_id,
+                allowance,
+            } => self.internal_set_minter_allowance(account_id, allowance),
+            MultisigAction::AddSigner { account_id } => {
+                self.multisig.signers.insert(&account_id);
+            }
+            MultisigAction::RemoveSigner { account_id } => {
+                require!(
+                    self.multisig.signers.remove(&account_id),
+                    "Account is not a signer"
+                );
+                self.internal_set_threshold(self.multisig.threshold);
+            }
+            MultisigAction::SetThreshold { threshold } => self.internal_set_threshold(threshold),
+        }
+    }
+}
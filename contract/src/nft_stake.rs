@@ -0,0 +1,277 @@
+use super::*;
+use crate::stake_events::NftStake;
+use near_contract_standards::non_fungible_token::core::NonFungibleTokenReceiver;
+use near_contract_standards::non_fungible_token::TokenId;
+use near_sdk::{assert_one_yocto, ext_contract, is_promise_success, Promise, ONE_YOCTO};
+use serde::{Deserialize, Serialize};
+
+#[ext_contract(ext_nft_contract)]
+pub trait NftCollectionContract {
+    fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    );
+}
+#[ext_contract(ext_factory_contract)]
+pub trait Factory {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+#[ext_contract(ext_self)]
+pub trait NftStakeCallBack {
+    fn nft_unstake_call_back(&mut self, account_id: AccountId, staked_nft: StakedNft);
+    fn nft_claim_call_back(&mut self, account_id: AccountId, reward: Balance);
+}
+
+/// Whitelisted NEP-171 collection, `reward_rate` is in cookies per token per minute.
+/// `acc_reward_per_token` is the reward a single token earned up to `last_update_time`.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Debug)]
+pub struct NftCollection {
+    pub reward_rate: Balance,
+    pub total_staked: u64,
+    pub acc_reward_per_token: Balance,
+    pub last_update_time: u64,
+}
+impl NftCollection {
+    pub fn new(reward_rate: Balance) -> Self {
+        Self {
+            reward_rate,
+            total_staked: 0,
+            acc_reward_per_token: 0,
+            last_update_time: env::block_timestamp(),
+        }
+    }
+    /// Reward per token up to now, counted in whole minutes.
+    pub fn reward_per_token(&self) -> Balance {
+        self.acc_reward_per_token + self.reward_rate * self.minutes_elapsed() as u128
+    }
+    /// Settles the elapsed minutes at the current rate, must run before the rate changes.
+    pub fn checkpoint(&mut self) {
+        self.acc_reward_per_token = self.reward_per_token();
+        //Only whole minutes are consumed so partial minutes keep counting
+        self.last_update_time += self.minutes_elapsed() * NANOS_PER_MINUTE;
+    }
+    fn minutes_elapsed(&self) -> u64 {
+        (env::block_timestamp() - self.last_update_time) / NANOS_PER_MINUTE
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, Clone)]
+pub struct StakedNft {
+    pub collection_id: AccountId,
+    pub token_id: TokenId,
+    pub staked_at: u64,
+    /// Collection reward per token already paid into the staker's `acc_reward`.
+    pub reward_debt: Balance,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Debug, Default)]
+pub struct NftStaker {
+    pub tokens: Vec<StakedNft>,
+    pub acc_reward: Balance,
+}
+
+#[near_bindgen]
+impl NonFungibleTokenReceiver for Contract {
+    fn nft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_id: AccountId,
+        token_id: TokenId,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        self.assert_not_paused(Feature::Staking);
+        let collection_id = env::predecessor_account_id();
+        let mut collection = self
+            .nft_collections
+            .get(&collection_id)
+            .unwrap_or_else(|| panic!("Collection is not whitelisted for staking"));
+        log!(
+            "Stake {} from {} sent by {} with msg {}",
+            token_id,
+            previous_owner_id,
+            sender_id,
+            msg
+        );
+        let mut staker = match self.nft_stake.get(&previous_owner_id) {
+            Some(mut staker) => {
+                self.update_nft_reward(&mut staker);
+                staker
+            }
+            None => NftStaker::default(),
+        };
+        staker.tokens.push(StakedNft {
+            collection_id: collection_id.clone(),
+            token_id: token_id.clone(),
+            staked_at: env::block_timestamp(),
+            reward_debt: collection.reward_per_token(),
+        });
+        self.nft_stake.insert(&previous_owner_id, &staker);
+        collection.total_staked += 1;
+        self.nft_collections.insert(&collection_id, &collection);
+
+        NftStake {
+            account_id: &previous_owner_id,
+            collection_id: &collection_id,
+            token_id: &token_id,
+        }
+        .emit_staked();
+        PromiseOrValue::Value(false)
+    }
+}
+
+//Call Methods
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn nft_unstake(&mut self, collection_id: AccountId, token_id: TokenId) -> Promise {
+        assert_one_yocto();
+        self.assert_not_paused(Feature::Unstaking);
+        let account_id = env::predecessor_account_id();
+        let mut staker = self
+            .nft_stake
+            .get(&account_id)
+            .unwrap_or_else(|| panic!("No NFT stake record found for this account"));
+        let index = staker
+            .tokens
+            .iter()
+            .position(|nft| nft.collection_id == collection_id && nft.token_id == token_id)
+            .unwrap_or_else(|| panic!("Token is not staked by this account"));
+        self.update_nft_reward(&mut staker);
+        let staked_nft = staker.tokens.remove(index);
+        self.nft_stake.insert(&account_id, &staker);
+        if let Some(mut collection) = self.nft_collections.get(&collection_id) {
+            collection.total_staked -= 1;
+            self.nft_collections.insert(&collection_id, &collection);
+        }
+
+        NftStake {
+            account_id: &account_id,
+            collection_id: &collection_id,
+            token_id: &token_id,
+        }
+        .emit_unstaked();
+
+        ext_nft_contract::nft_transfer(
+            account_id.clone(),
+            token_id,
+            None,
+            Some("Unstake".to_string()),
+            collection_id,
+            ONE_YOCTO,
+            env::prepaid_gas() / 3,
+        )
+        .then(ext_self::nft_unstake_call_back(
+            account_id,
+            staked_nft,
+            env::current_account_id(),
+            0,
+            env::prepaid_gas() / 3,
+        ))
+    }
+    #[payable]
+    pub fn claim_nft_cookie(&mut self) -> Promise {
+        assert_one_yocto();
+        self.assert_not_paused(Feature::Claiming);
+        let account_id = env::predecessor_account_id();
+        let mut staker = self
+            .nft_stake
+            .get(&account_id)
+            .unwrap_or_else(|| panic!("No NFT stake record found for this account"));
+        self.update_nft_reward(&mut staker);
+        let reward = staker.acc_reward;
+        require!(reward > 0, "No reward to claim");
+        staker.acc_reward = 0;
+        self.nft_stake.insert(&account_id, &staker);
+
+        ext_factory_contract::ft_transfer(
+            account_id.clone(),
+            U128(reward),
+            None,
+            self.config.factory_id.clone(),
+            ONE_YOCTO,
+            env::prepaid_gas() / 3,
+        )
+        .then(ext_self::nft_claim_call_back(
+            account_id,
+            reward,
+            env::current_account_id(),
+            0,
+            env::prepaid_gas() / 3,
+        ))
+    }
+    #[private]
+    pub fn nft_unstake_call_back(&mut self, account_id: AccountId, staked_nft: StakedNft) {
+        if is_promise_success() {
+            return;
+        }
+        //Transfer failed, the token is still held by this contract
+        let mut staker = match self.nft_stake.get(&account_id) {
+            Some(mut staker) => {
+                self.update_nft_reward(&mut staker);
+                staker
+            }
+            None => NftStaker::default(),
+        };
+        //The reward up to the unstake was already paid
+        let reward_debt = match self.nft_collections.get(&staked_nft.collection_id) {
+            Some(mut collection) => {
+                collection.total_staked += 1;
+                self.nft_collections
+                    .insert(&staked_nft.collection_id, &collection);
+                collection.reward_per_token()
+            }
+            None => staked_nft.reward_debt,
+        };
+        NftStake {
+            account_id: &account_id,
+            collection_id: &staked_nft.collection_id,
+            token_id: &staked_nft.token_id,
+        }
+        .emit_staked();
+        staker.tokens.push(StakedNft {
+            reward_debt,
+            ..staked_nft
+        });
+        self.nft_stake.insert(&account_id, &staker);
+    }
+    #[private]
+    pub fn nft_claim_call_back(&mut self, account_id: AccountId, reward: Balance) {
+        if is_promise_success() {
+            return;
+        }
+        //Transfer failed, give the reward back
+        let mut staker = self.nft_stake.get(&account_id).unwrap_or_default();
+        staker.acc_reward += reward;
+        self.nft_stake.insert(&account_id, &staker);
+        log!("Claim of {} NFT reward failed, reward restored", reward);
+    }
+}
+
+impl Contract {
+    /// Reward of all tokens staked by `staker` since they were last settled.
+    pub(crate) fn cal_nft_reward(&self, staker: &NftStaker) -> Balance {
+        staker
+            .tokens
+            .iter()
+            .map(|nft| {
+                self.nft_collections
+                    .get(&nft.collection_id)
+                    .map(|collection| collection.reward_per_token() - nft.reward_debt)
+                    .unwrap_or(0)
+            })
+            .sum()
+    }
+    pub(crate) fn update_nft_reward(&self, staker: &mut NftStaker) {
+        staker.acc_reward += self.cal_nft_reward(staker);
+        for nft in staker.tokens.iter_mut() {
+            if let Some(collection) = self.nft_collections.get(&nft.collection_id) {
+                nft.reward_debt = collection.reward_per_token();
+            }
+        }
+    }
+}
+
+const NANOS_PER_MINUTE: u64 = 1000000000 * 60;
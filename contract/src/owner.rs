@@ -1,20 +1,369 @@
 use super::*;
+use crate::events::{OwnerChange, RoleChange};
+use crate::stake_events::RateChanged;
+use near_contract_standards::fungible_token::tax::MAX_TAX_RATE_BPS;
 #[near_bindgen]
 impl Contract {
-    pub fn assert_owner_signer(&self) {
+    //Ownership
+    /// Proposes `owner_id` as the next owner, who takes over by calling `accept_owner`.
+    pub fn propose_owner(&mut self, owner_id: AccountId) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_propose_owner(owner_id);
+    }
+
+    /// The owner roles of the previous owner move to the new owner.
+    pub fn accept_owner(&mut self) {
+        let owner_id = env::predecessor_account_id();
         require!(
-            self.owner_id == env::signer_account_id(),
-            "Assert owner failed"
+            self.pending_owner_id.as_ref() == Some(&owner_id),
+            "Only the pending owner can accept ownership"
         );
+        OwnerChange {
+            old_owner_id: self.owner_id.as_ref(),
+            new_owner_id: Some(&owner_id),
+        }
+        .emit_transferred();
+        self.internal_move_owner_roles(Some(&owner_id));
+        self.upgrade.owner = Some(owner_id.clone());
+        self.owner_id = Some(owner_id);
+        self.pending_owner_id = None;
+    }
+
+    pub fn cancel_owner_proposal(&mut self) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_cancel_owner_proposal();
+    }
+
+    /// Leaves the contract without an owner for good, owner-only methods can't be called
+    /// anymore and the owner roles of the last owner are revoked.
+    pub fn renounce_ownership(&mut self) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_renounce_ownership();
+    }
+
+    //Tax Exemptions
+    pub fn set_tax_exempt_sender(&mut self, account_id: AccountId, exempt: bool) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_set_tax_exempt_sender(account_id, exempt);
+    }
+    pub fn set_tax_exempt_receiver(&mut self, account_id: AccountId, exempt: bool) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_set_tax_exempt_receiver(account_id, exempt);
+    }
+    /// Charges `rate_bps` on transfers from or to `account_id`, `None` restores the policy rate.
+    pub fn set_custom_tax_rate(&mut self, account_id: AccountId, rate_bps: Option<u16>) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_set_custom_tax_rate(account_id, rate_bps);
+    }
+
+    pub fn set_unbonding_period(&mut self, period: u64) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_set_unbonding_period(period);
+    }
+
+    //Lockup Tiers
+    pub fn set_lock_tiers(&mut self, tiers: Vec<LockTier>) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_set_lock_tiers(tiers);
     }
 
-    pub fn set_reward_rate(&mut self, rate: u8) {
-        self.assert_owner_signer();
-        self.cookie_reward_rate = rate;
+    //Pool Registry
+    pub fn add_pool(&mut self, token_id: AccountId, reward_rate: u8, fee_percent: u8) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_add_pool(token_id, reward_rate, fee_percent);
     }
 
-    pub fn set_bake_fee(&mut self, rate: u8) {
-        self.assert_owner_signer();
-        self.fee_percent = rate;
+    pub fn update_pool(&mut self, token_id: AccountId, reward_rate: u8, fee_percent: u8) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_update_pool(token_id, reward_rate, fee_percent);
+    }
+
+    pub fn remove_pool(&mut self, token_id: AccountId) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_remove_pool(token_id);
+    }
+
+    //NFT Collection Registry
+    pub fn add_nft_collection(&mut self, collection_id: AccountId, reward_rate: U128) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_add_nft_collection(collection_id, reward_rate);
+    }
+
+    pub fn update_nft_collection(&mut self, collection_id: AccountId, reward_rate: U128) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_update_nft_collection(collection_id, reward_rate);
+    }
+
+    pub fn remove_nft_collection(&mut self, collection_id: AccountId) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.internal_remove_nft_collection(collection_id);
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_propose_owner(&mut self, owner_id: AccountId) {
+        OwnerChange {
+            old_owner_id: self.owner_id.as_ref(),
+            new_owner_id: Some(&owner_id),
+        }
+        .emit_proposed();
+        self.pending_owner_id = Some(owner_id);
+    }
+    pub(crate) fn internal_cancel_owner_proposal(&mut self) {
+        let pending_owner_id = self
+            .pending_owner_id
+            .take()
+            .unwrap_or_else(|| panic!("No pending owner"));
+        OwnerChange {
+            old_owner_id: Some(&pending_owner_id),
+            new_owner_id: None,
+        }
+        .emit_proposal_cancelled();
+    }
+    pub(crate) fn internal_renounce_ownership(&mut self) {
+        OwnerChange {
+            old_owner_id: self.owner_id.as_ref(),
+            new_owner_id: None,
+        }
+        .emit_transferred();
+        self.internal_move_owner_roles(None);
+        self.upgrade.owner = None;
+        self.owner_id = None;
+        self.pending_owner_id = None;
+    }
+    pub(crate) fn internal_set_tax_exempt_sender(&mut self, account_id: AccountId, exempt: bool) {
+        if exempt {
+            self.token.tax_exemptions.senders.insert(&account_id);
+        } else {
+            self.token.tax_exemptions.senders.remove(&account_id);
+        }
+    }
+    pub(crate) fn internal_set_tax_exempt_receiver(&mut self, account_id: AccountId, exempt: bool) {
+        if exempt {
+            self.token.tax_exemptions.receivers.insert(&account_id);
+        } else {
+            self.token.tax_exemptions.receivers.remove(&account_id);
+        }
+    }
+    pub(crate) fn internal_set_custom_tax_rate(
+        &mut self,
+        account_id: AccountId,
+        rate_bps: Option<u16>,
+    ) {
+        match rate_bps {
+            Some(rate_bps) => {
+                require!(rate_bps <= MAX_TAX_RATE_BPS, "Tax rate exceeds the maximum");
+                self.token
+                    .tax_exemptions
+                    .custom_rates
+                    .insert(&account_id, &rate_bps);
+            }
+            None => {
+                self.token.tax_exemptions.custom_rates.remove(&account_id);
+            }
+        }
+    }
+    pub(crate) fn internal_set_unbonding_period(&mut self, period: u64) {
+        self.config.unbonding_period = period;
+    }
+    pub(crate) fn internal_set_lock_tiers(&mut self, tiers: Vec<LockTier>) {
+        Config::assert_lock_tiers(&tiers);
+        self.config.lock_tiers = tiers;
+    }
+    pub(crate) fn internal_add_pool(
+        &mut self,
+        token_id: AccountId,
+        reward_rate: u8,
+        fee_percent: u8,
+    ) {
+        require!(fee_percent <= 100, "Fee percent must not exceed 100");
+        require!(
+            self.pools.get(&token_id).is_none(),
+            "Pool already exists for this token"
+        );
+        let pool = Pool::new(&token_id, reward_rate, fee_percent);
+        self.pools.insert(&token_id, &pool);
+    }
+    pub(crate) fn internal_update_pool(
+        &mut self,
+        token_id: AccountId,
+        reward_rate: u8,
+        fee_percent: u8,
+    ) {
+        require!(fee_percent <= 100, "Fee percent must not exceed 100");
+        let mut pool = self.internal_get_pool(&token_id);
+        pool.checkpoint();
+        pool.reward_rate = reward_rate;
+        pool.fee_percent = fee_percent;
+        self.pools.insert(&token_id, &pool);
+    }
+    pub(crate) fn internal_remove_pool(&mut self, token_id: AccountId) {
+        let mut pool = self.internal_get_pool(&token_id);
+        require!(
+            pool.reward_index.total_shares == 0 && pool.fee_collected == 0,
+            "Pool still holds tokens"
+        );
+        pool.stake.clear();
+        self.pools.remove(&token_id);
+    }
+    pub(crate) fn internal_add_nft_collection(
+        &mut self,
+        collection_id: AccountId,
+        reward_rate: U128,
+    ) {
+        require!(
+            self.nft_collections.get(&collection_id).is_none(),
+            "Collection already whitelisted"
+        );
+        let collection = NftCollection::new(reward_rate.0);
+        self.nft_collections.insert(&collection_id, &collection);
+    }
+    pub(crate) fn internal_update_nft_collection(
+        &mut self,
+        collection_id: AccountId,
+        reward_rate: U128,
+    ) {
+        let mut collection = self
+            .nft_collections
+            .get(&collection_id)
+            .unwrap_or_else(|| panic!("Collection is not whitelisted"));
+        //Elapsed time keeps the old rate
+        collection.checkpoint();
+        collection.reward_rate = reward_rate.0;
+        self.nft_collections.insert(&collection_id, &collection);
+    }
+    pub(crate) fn internal_remove_nft_collection(&mut self, collection_id: AccountId) {
+        let collection = self
+            .nft_collections
+            .get(&collection_id)
+            .unwrap_or_else(|| panic!("Collection is not whitelisted"));
+        require!(
+            collection.total_staked == 0,
+            "Collection still has staked tokens"
+        );
+        self.nft_collections.remove(&collection_id);
+    }
+    /// Starts a new emission epoch at `rate` now, epochs scheduled later are kept.
+    pub(crate) fn internal_set_reward_rate(&mut self, rate: u8) {
+        self.config.assert_reward_rate(rate);
+        self.checkpoint_reward();
+        let now = env::block_timestamp();
+        self.emission.epochs.retain(|epoch| epoch.start_time != now);
+        let index = self
+            .emission
+            .epochs
+            .iter()
+            .position(|epoch| epoch.start_time > now)
+            .unwrap_or(self.emission.epochs.len());
+        self.emission.epochs.insert(
+            index,
+            Epoch {
+                start_time: now,
+                reward_rate: rate,
+            },
+        );
+        RateChanged {
+            start_time: now,
+            reward_rate: rate,
+        }
+        .emit();
+    }
+    /// Builds the schedule set by an `EmissionSchedule` change, the emitted total is kept.
+    pub(crate) fn internal_emission_schedule(
+        &self,
+        epochs: Vec<Epoch>,
+        halving_interval: u64,
+        max_emission: Option<U128>,
+    ) -> EmissionSchedule {
+        for epoch in epochs.iter() {
+            self.config.assert_reward_rate(epoch.reward_rate);
+        }
+        let schedule = EmissionSchedule {
+            epochs,
+            halving_interval,
+            max_emission: max_emission.map(|max_emission| max_emission.0),
+            total_emitted: self.emission.total_emitted,
+        };
+        schedule.assert_valid();
+        schedule
+    }
+    pub(crate) fn internal_set_emission_schedule(
+        &mut self,
+        epochs: Vec<Epoch>,
+        halving_interval: u64,
+        max_emission: Option<U128>,
+    ) {
+        self.checkpoint_reward();
+        let schedule = self.internal_emission_schedule(epochs, halving_interval, max_emission);
+        RateChanged::emit_many(
+            &schedule
+                .epochs
+                .iter()
+                .map(|epoch| RateChanged {
+                    start_time: epoch.start_time,
+                    reward_rate: epoch.reward_rate,
+                })
+                .collect::<Vec<_>>(),
+        );
+        self.emission = schedule;
+    }
+    pub(crate) fn internal_update_vault(&mut self, vault_id: AccountId) {
+        self.config.vault_id = vault_id.clone();
+        self.token.vault = vault_id.clone();
+        if !self.token.accounts.contains_key(&vault_id) {
+            self.token.internal_register_account(&vault_id);
+        }
+        self.token.tax_exemptions.senders.insert(&vault_id);
+        self.token.tax_exemptions.receivers.insert(&vault_id);
+        env::log_str("update vault");
+    }
+    /// Revokes the owner roles held by the current owner and grants them to `new_owner_id`.
+    fn internal_move_owner_roles(&mut self, new_owner_id: Option<&AccountId>) {
+        let old_owner_id = match self.owner_id.clone() {
+            Some(owner_id) => owner_id,
+            None => return,
+        };
+        let by = env::predecessor_account_id();
+        for role in OWNER_ROLES {
+            if !self.internal_revoke_role(&old_owner_id, role) {
+                continue;
+            }
+            RoleChange {
+                account_id: &old_owner_id,
+                role: &role,
+                by: &by,
+            }
+            .emit_revoked();
+            if let Some(new_owner_id) = new_owner_id {
+                self.internal_grant_role(new_owner_id, role);
+                RoleChange {
+                    account_id: new_owner_id,
+                    role: &role,
+                    by: &by,
+                }
+                .emit_granted();
+            }
+        }
+    }
+    /// Owner check on the direct caller, so the owner can be a DAO or multisig contract.
+    pub(crate) fn assert_owner_predecessor(&self) {
+        require!(
+            self.owner_id == Some(env::predecessor_account_id()),
+            "Assert owner failed"
+        );
     }
 }
@@ -0,0 +1,71 @@
+use super::*;
+use crate::access::Role;
+use crate::events::PauseChange;
+use serde::{Deserialize, Serialize};
+
+/// Group of entry points that can be halted together.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    Transfers,
+    Staking,
+    Claiming,
+    Unstaking,
+    Minting,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Default, Debug, Clone)]
+pub struct PauseFlags {
+    pub transfers: bool,
+    pub staking: bool,
+    pub claiming: bool,
+    pub unstaking: bool,
+    pub minting: bool,
+}
+impl PauseFlags {
+    pub fn is_paused(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::Transfers => self.transfers,
+            Feature::Staking => self.staking,
+            Feature::Claiming => self.claiming,
+            Feature::Unstaking => self.unstaking,
+            Feature::Minting => self.minting,
+        }
+    }
+    fn flag_mut(&mut self, feature: Feature) -> &mut bool {
+        match feature {
+            Feature::Transfers => &mut self.transfers,
+            Feature::Staking => &mut self.staking,
+            Feature::Claiming => &mut self.claiming,
+            Feature::Unstaking => &mut self.unstaking,
+            Feature::Minting => &mut self.minting,
+        }
+    }
+}
+
+//Call Methods
+#[near_bindgen]
+impl Contract {
+    pub fn set_paused(&mut self, feature: Feature, paused: bool) {
+        self.assert_role(Role::Pauser);
+        let flag = self.paused.flag_mut(feature);
+        if *flag == paused {
+            return;
+        }
+        *flag = paused;
+        PauseChange {
+            feature: &feature,
+            paused,
+            by: &env::predecessor_account_id(),
+        }
+        .emit();
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_not_paused(&self, feature: Feature) {
+        if self.paused.is_paused(feature) {
+            panic!("{:?} is paused", feature);
+        }
+    }
+}
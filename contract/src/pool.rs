@@ -0,0 +1,288 @@
+use super::*;
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_sdk::{assert_one_yocto, ext_contract, is_promise_success, Promise, ONE_YOCTO};
+use serde::{Deserialize, Serialize};
+
+#[ext_contract(ext_pool_token)]
+pub trait PoolToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+#[ext_contract(ext_factory_contract)]
+pub trait Factory {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+#[ext_contract(ext_self)]
+pub trait PoolCallBack {
+    fn pool_unstake_call_back(
+        &mut self,
+        token_id: AccountId,
+        account_id: AccountId,
+        amount: Balance,
+    );
+    fn pool_claim_call_back(&mut self, token_id: AccountId, account_id: AccountId, reward: Balance);
+    fn pool_fee_call_back(&mut self, token_id: AccountId, amount: Balance);
+}
+
+/// Staking pool for a whitelisted NEP-141 token. Stakes are received through
+/// `ft_transfer_call` and earn cookies at the pool's own reward rate.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Pool {
+    pub reward_rate: u8,
+    pub fee_percent: u8,
+    pub fee_collected: Balance,
+    pub reward_index: RewardIndex,
+    pub stake: UnorderedMap<AccountId, StakeV2>,
+}
+impl Pool {
+    pub fn new(token_id: &AccountId, reward_rate: u8, fee_percent: u8) -> Self {
+        Self {
+            reward_rate,
+            fee_percent,
+            fee_collected: 0,
+            reward_index: RewardIndex::default(),
+            stake: UnorderedMap::new(StorageKey::PoolStake {
+                token_id: token_id.clone(),
+            }),
+        }
+    }
+    /// Brings the pool reward index up to date with the pool reward rate.
+    pub fn checkpoint(&mut self) {
+        self.reward_index.checkpoint(self.reward_rate as u128);
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct PoolView {
+    pub token_id: AccountId,
+    pub reward_rate: u8,
+    pub fee_percent: u8,
+    pub total_stake: Balance,
+    pub fee_collected: Balance,
+    pub stakers: u64,
+}
+impl PoolView {
+    pub fn from_pool(token_id: AccountId, pool: &Pool) -> Self {
+        Self {
+            token_id,
+            reward_rate: pool.reward_rate,
+            fee_percent: pool.fee_percent,
+            total_stake: pool.reward_index.total_shares,
+            fee_collected: pool.fee_collected,
+            stakers: pool.stake.len(),
+        }
+    }
+}
+
+/// Message expected in `ft_transfer_call`, e.g. `{"action":"stake"}`.
+#[derive(Deserialize, Debug)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PoolMessage {
+    Stake,
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for Contract {
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        self.assert_not_paused(Feature::Staking);
+        let token_id = env::predecessor_account_id();
+        let mut pool = self
+            .pools
+            .get(&token_id)
+            .unwrap_or_else(|| panic!("Token is not whitelisted for staking"));
+        match serde_json::from_str::<PoolMessage>(&msg)
+            .unwrap_or_else(|_| panic!("Invalid stake message"))
+        {
+            PoolMessage::Stake => {
+                let fee = (amount.0 * pool.fee_percent as u128)
+                    .checked_div(100)
+                    .unwrap();
+                let amount_after_fee = amount.0 - fee;
+                require!(amount_after_fee > 0, "Stake amount too small");
+
+                pool.checkpoint();
+                let mut stake_data = pool.stake.get(&sender_id).unwrap_or_default();
+                stake_data.deposit(amount_after_fee, &mut pool.reward_index);
+                pool.stake.insert(&sender_id, &stake_data);
+                pool.fee_collected += fee;
+                self.pools.insert(&token_id, &pool);
+                PromiseOrValue::Value(U128(0))
+            }
+        }
+    }
+}
+
+//Call Methods
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn unstake_pool(&mut self, token_id: AccountId, amount: U128) -> Promise {
+        assert_one_yocto();
+        self.assert_not_paused(Feature::Unstaking);
+        let account_id = env::predecessor_account_id();
+        let mut pool = self.internal_get_pool(&token_id);
+        let mut stake_data = pool
+            .stake
+            .get(&account_id)
+            .unwrap_or_else(|| panic!("No stake record found for this account"));
+        require!(amount.0 > 0, "The amount should be a positive number");
+        require!(
+            stake_data.total_stake >= amount.0,
+            "Insufficient stake for unstaking"
+        );
+        pool.checkpoint();
+        stake_data.withdraw(amount.0, &mut pool.reward_index);
+        pool.stake.insert(&account_id, &stake_data);
+        self.pools.insert(&token_id, &pool);
+
+        ext_pool_token::ft_transfer(
+            account_id.clone(),
+            amount,
+            None,
+            token_id.clone(),
+            ONE_YOCTO,
+            env::prepaid_gas() / 3,
+        )
+        .then(ext_self::pool_unstake_call_back(
+            token_id,
+            account_id,
+            amount.0,
+            env::current_account_id(),
+            0,
+            env::prepaid_gas() / 3,
+        ))
+    }
+    #[payable]
+    pub fn claim_pool_reward(&mut self, token_id: AccountId) -> Promise {
+        assert_one_yocto();
+        self.assert_not_paused(Feature::Claiming);
+        let account_id = env::predecessor_account_id();
+        let mut pool = self.internal_get_pool(&token_id);
+        let mut stake_data = pool
+            .stake
+            .get(&account_id)
+            .unwrap_or_else(|| panic!("No stake record found for this account"));
+        pool.checkpoint();
+        stake_data.update_reward(&pool.reward_index);
+        let reward_before_fee = stake_data.acc_reward;
+        require!(reward_before_fee > 0, "No reward to claim");
+        let total_fee = (reward_before_fee * (pool.fee_percent as u128))
+            .checked_div(100)
+            .unwrap_or_else(|| panic!("Divide overflow check fail"));
+        let reward_after_fee = reward_before_fee - total_fee;
+        stake_data.acc_reward = 0;
+        pool.stake.insert(&account_id, &stake_data);
+        self.pools.insert(&token_id, &pool);
+
+        ext_factory_contract::ft_transfer(
+            account_id.clone(),
+            U128(reward_after_fee),
+            None,
+            self.config.factory_id.clone(),
+            ONE_YOCTO,
+            env::prepaid_gas() / 3,
+        )
+        .then(ext_self::pool_claim_call_back(
+            token_id,
+            account_id,
+            reward_before_fee,
+            env::current_account_id(),
+            0,
+            env::prepaid_gas() / 3,
+        ))
+    }
+    #[payable]
+    pub fn withdraw_pool_fee(&mut self, token_id: AccountId) -> Promise {
+        assert_one_yocto();
+        self.assert_role(Role::FeeManager);
+        let mut pool = self.internal_get_pool(&token_id);
+        let amount = pool.fee_collected;
+        require!(amount > 0, "No fee to withdraw");
+        pool.fee_collected = 0;
+        self.pools.insert(&token_id, &pool);
+
+        ext_pool_token::ft_transfer(
+            self.token.vault.clone(),
+            U128(amount),
+            Some("Pool fee".to_string()),
+            token_id.clone(),
+            ONE_YOCTO,
+            env::prepaid_gas() / 3,
+        )
+        .then(ext_self::pool_fee_call_back(
+            token_id,
+            amount,
+            env::current_account_id(),
+            0,
+            env::prepaid_gas() / 3,
+        ))
+    }
+    #[private]
+    pub fn pool_unstake_call_back(
+        &mut self,
+        token_id: AccountId,
+        account_id: AccountId,
+        amount: Balance,
+    ) {
+        if is_promise_success() {
+            return;
+        }
+        //Transfer failed, restore the stake
+        let mut pool = self.internal_get_pool(&token_id);
+        pool.checkpoint();
+        let mut stake_data = pool.stake.get(&account_id).unwrap_or_default();
+        stake_data.deposit(amount, &mut pool.reward_index);
+        pool.stake.insert(&account_id, &stake_data);
+        self.pools.insert(&token_id, &pool);
+        log!(
+            "Unstake of {} from pool {} failed, stake restored",
+            amount,
+            token_id
+        );
+    }
+    #[private]
+    pub fn pool_claim_call_back(
+        &mut self,
+        token_id: AccountId,
+        account_id: AccountId,
+        reward: Balance,
+    ) {
+        if is_promise_success() {
+            return;
+        }
+        //Transfer failed, give the reward back
+        let mut pool = self.internal_get_pool(&token_id);
+        if let Some(mut stake_data) = pool.stake.get(&account_id) {
+            stake_data.acc_reward += reward;
+            pool.stake.insert(&account_id, &stake_data);
+            self.pools.insert(&token_id, &pool);
+        }
+        log!(
+            "Claim of {} from pool {} failed, reward restored",
+            reward,
+            token_id
+        );
+    }
+    #[private]
+    pub fn pool_fee_call_back(&mut self, token_id: AccountId, amount: Balance) {
+        if is_promise_success() {
+            return;
+        }
+        let mut pool = self.internal_get_pool(&token_id);
+        pool.fee_collected += amount;
+        self.pools.insert(&token_id, &pool);
+        log!("Fee withdrawal of {} from pool {} failed", amount, token_id);
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_get_pool(&self, token_id: &AccountId) -> Pool {
+        self.pools
+            .get(token_id)
+            .unwrap_or_else(|| panic!("Pool not found for this token"))
+    }
+}
@@ -0,0 +1,96 @@
+use super::*;
+use near_sdk::assert_one_yocto;
+use serde::Serialize;
+
+/// NEKO held by this contract for stakers against the NEKO they staked.
+#[derive(Serialize, Debug)]
+pub struct ReserveView {
+    pub reserve: U128,
+    pub total_staked: U128,
+    pub total_shortfall: U128,
+    /// `reserve` over `total_staked` in basis points, 10000 while nothing is staked.
+    pub reserve_ratio_bps: U128,
+}
+
+//Call Methods
+#[near_bindgen]
+impl Contract {
+    /// Moves NEKO of the caller into the staking reserve.
+    #[payable]
+    pub fn fund_reserve(&mut self, amount: U128) {
+        assert_one_yocto();
+        self.token.internal_transfer(
+            &env::predecessor_account_id(),
+            &env::current_account_id(),
+            amount.0,
+            Some("Fund reserve".to_string()),
+        );
+        self.staking_reserve += amount.0;
+    }
+    /// Pays the queued shortfall of the caller as far as the reserve allows.
+    #[payable]
+    pub fn claim_shortfall(&mut self) -> U128 {
+        assert_one_yocto();
+        self.assert_not_paused(Feature::Unstaking);
+        let account_id = env::predecessor_account_id();
+        let owed = self
+            .shortfall
+            .get(&account_id)
+            .unwrap_or_else(|| panic!("No shortfall found for this account"));
+        let paid = std::cmp::min(owed, self.staking_reserve);
+        require!(paid > 0, "Staking reserve is empty");
+
+        if owed > paid {
+            self.shortfall.insert(&account_id, &(owed - paid));
+        } else {
+            self.shortfall.remove(&account_id);
+        }
+        self.total_shortfall -= paid;
+        self.staking_reserve -= paid;
+        self.token.internal_transfer(
+            &env::current_account_id(),
+            &account_id,
+            paid,
+            Some("Shortfall".to_string()),
+        );
+        U128(paid)
+    }
+}
+
+impl Contract {
+    /// Pays `amount` redeemed by `account_id` out of the reserve and queues what the
+    /// reserve can't cover. Returns the amount paid.
+    pub(crate) fn internal_redeem(&mut self, account_id: &AccountId, amount: Balance) -> Balance {
+        let paid = std::cmp::min(amount, self.staking_reserve);
+        self.staking_reserve -= paid;
+        if paid > 0 {
+            self.internal_release_unstaked(account_id, paid);
+        }
+        self.internal_queue_shortfall(account_id, amount - paid);
+        paid
+    }
+    /// Records `amount` that is owed to `account_id` until the reserve is refilled.
+    pub(crate) fn internal_queue_shortfall(&mut self, account_id: &AccountId, amount: Balance) {
+        if amount > 0 {
+            let owed = self.shortfall.get(account_id).unwrap_or(0);
+            self.shortfall.insert(account_id, &(owed + amount));
+            self.total_shortfall += amount;
+            log!("Queued shortfall of {} for {}", amount, account_id);
+        }
+    }
+    /// Reserve NEKO that doesn't back any stake yet.
+    pub(crate) fn reserve_surplus(&self) -> Balance {
+        self.staking_reserve.saturating_sub(self.total_staked)
+    }
+    pub(crate) fn internal_reserve_view(&self) -> ReserveView {
+        let reserve_ratio_bps = (self.staking_reserve * 10_000)
+            .checked_div(self.total_staked)
+            .unwrap_or(10_000);
+        ReserveView {
+            reserve: U128(self.staking_reserve),
+            total_staked: U128(self.total_staked),
+            total_shortfall: U128(self.total_shortfall),
+            reserve_ratio_bps: U128(reserve_ratio_bps),
+        }
+    }
+}
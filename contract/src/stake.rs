@@ -1,6 +1,9 @@
 use super::*;
-/* use near_contract_standards::fungible_token::events::FtStake; */
-use near_sdk::{assert_one_yocto, ext_contract, Promise, ONE_YOCTO};
+use crate::lockup::{LockPosition, LockTier};
+use crate::stake_events::{Claim, FeeCharged, Stake, StakeFailed, Unstake};
+use near_sdk::{
+    assert_one_yocto, ext_contract, is_promise_success, Promise, PromiseResult, ONE_YOCTO,
+};
 use serde::Serialize;
 
 #[ext_contract(ext_factory_contract)]
@@ -11,63 +14,197 @@ pub trait Factory {
 }
 #[ext_contract(ext_self)]
 pub trait NekoStakeCallBack {
-    fn neko_stake_call_back(&mut self, to: AccountId, amount: Balance, fee: Balance);
-    fn claim_reward_call_back(&mut self);
-    fn cookie_exchange_call_back(&mut self);
+    fn neko_stake_call_back(
+        &mut self,
+        to: AccountId,
+        amount: Balance,
+        fee: Balance,
+        lock_tier: Option<u8>,
+    );
+    fn claim_reward_call_back(&mut self, account_id: AccountId, reward: Balance);
+    fn cookie_exchange_call_back(&mut self, account_id: AccountId, amount: Balance);
+}
+
+/// Fixed point precision of `acc_reward_per_share`.
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+pub const NANOS_PER_MINUTE: u64 = 1_000_000_000 * 60;
+
+/// Global reward-per-share index. It has to be checkpointed with the rate in
+/// force before the rate or the total shares change, so every period is
+/// priced at the rate that applied during it.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Debug, Clone)]
+pub struct RewardIndex {
+    pub acc_reward_per_share: u128,
+    pub total_shares: Balance,
+    pub last_update_time: u64,
+}
+impl Default for RewardIndex {
+    fn default() -> Self {
+        Self {
+            acc_reward_per_share: 0,
+            total_shares: 0,
+            last_update_time: env::block_timestamp(),
+        }
+    }
+}
+impl RewardIndex {
+    fn minutes_elapsed(&self) -> u64 {
+        (env::block_timestamp() - self.last_update_time) / NANOS_PER_MINUTE
+    }
+    /// Reward per share as of now, `reward_rate` is percent of stake per minute.
+    pub fn accumulated(&self, reward_rate: u128) -> u128 {
+        let reward_add = (reward_rate * REWARD_PRECISION)
+            .checked_mul(self.minutes_elapsed() as u128)
+            .unwrap_or_else(|| panic!("Reward Per Share Calculation Overflow"))
+            / 100;
+        self.acc_reward_per_share + reward_add
+    }
+    pub fn checkpoint(&mut self, reward_rate: u128) {
+        let minutes = self.minutes_elapsed();
+        if minutes > 0 {
+            self.acc_reward_per_share = self.accumulated(reward_rate);
+            //Only whole minutes are consumed so partial minutes keep counting
+            self.last_update_time += minutes * NANOS_PER_MINUTE;
+        }
+    }
 }
 
-#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault, Serialize, Debug)]
-pub struct Stake {
+/// Stake record in the baseline layout, upgraded to [`StakeV2`] on first use.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Debug)]
+pub struct StakeV1 {
     pub total_stake: Balance,
     pub acc_reward: u128,
     pub last_update_time: u64,
 }
-impl Stake {
-    pub fn cal_reward(&self, reward_rate: u128) -> Balance {
-        let time_diff: u128 = (env::block_timestamp() - self.last_update_time) as u128;
-        let time_since_last_update_in_min: u128 = (time_diff)
-            .checked_div(1000000000 * 60)
-            .unwrap_or_else(|| panic!("Reward Time Calculation Overflow")); // 60 seconds
-        let reward_mul: u128 = (self.total_stake * reward_rate) as u128;
-        let reward_per_mins = (reward_mul)
-            .checked_div(100)
-            .unwrap_or_else(|| panic!("Reward Per Time Calculation Overflow"));
+impl StakeV1 {
+    /// Settles the reward earned at `reward_rate` until `until`. The shares were counted
+    /// in the reward index by the migration, which started it at 0 so no reward debt is owed.
+    pub fn upgrade(self, reward_rate: u8, until: u64) -> StakeV2 {
+        let minutes = until.saturating_sub(self.last_update_time) / NANOS_PER_MINUTE;
+        let reward = self.total_stake * reward_rate as u128 / 100 * minutes as u128;
+        StakeV2 {
+            total_stake: self.total_stake,
+            shares: self.total_stake,
+            acc_reward: self.acc_reward + reward,
+            ..Default::default()
+        }
+    }
+}
 
-        let reward_add: u128 = reward_per_mins * time_since_last_update_in_min;
+/// Stake of one account. `shares` is the stake weighted by the multiplier of
+/// each lock position and is what earns rewards. Stake outside of `positions`
+/// is flexible and earns at 1x.
+#[derive(BorshDeserialize, BorshSerialize, Default, Serialize, Debug)]
+pub struct StakeV2 {
+    pub total_stake: Balance,
+    pub shares: Balance,
+    pub acc_reward: u128,
+    pub reward_debt: u128,
+    pub positions: Vec<LockPosition>,
+    pub next_position_id: u64,
+    pub auto_compound: bool,
+}
+impl StakeV2 {
+    /// Reward earned since the last settlement for the given reward per share.
+    pub fn pending_reward(&self, acc_reward_per_share: u128) -> Balance {
+        self.shares * acc_reward_per_share / REWARD_PRECISION - self.reward_debt
+    }
+    /// Adds the reward earned since the last settlement to `acc_reward`.
+    pub fn update_reward(&mut self, index: &RewardIndex) -> Balance {
+        let reward_add = self.pending_reward(index.acc_reward_per_share);
+        self.acc_reward += reward_add;
+        self.reward_debt += reward_add;
         reward_add
     }
+    fn update_shares(&mut self, shares: Balance, index: &mut RewardIndex) {
+        index.total_shares = index.total_shares - self.shares + shares;
+        self.shares = shares;
+        self.reward_debt = self.shares * index.acc_reward_per_share / REWARD_PRECISION;
+    }
+    /// Stake that is not held in any lock position.
+    pub fn flexible_stake(&self) -> Balance {
+        self.total_stake
+            - self
+                .positions
+                .iter()
+                .map(|position| position.amount)
+                .sum::<Balance>()
+    }
+    pub fn deposit(&mut self, amount: Balance, index: &mut RewardIndex) {
+        self.update_reward(index);
+        self.total_stake += amount;
+        self.update_shares(self.shares + amount, index);
+    }
+    pub fn withdraw(&mut self, amount: Balance, index: &mut RewardIndex) {
+        require!(
+            amount <= self.flexible_stake(),
+            "Insufficient unlocked stake"
+        );
+        self.update_reward(index);
+        self.total_stake -= amount;
+        self.update_shares(self.shares - amount, index);
+    }
+    /// Locks `amount` in a new position, returns its id.
+    pub fn lock(&mut self, amount: Balance, tier: &LockTier, index: &mut RewardIndex) -> u64 {
+        self.update_reward(index);
+        let position = LockPosition::new(self.next_position_id, amount, tier);
+        self.next_position_id += 1;
+        self.total_stake += amount;
+        self.update_shares(self.shares + position.shares(), index);
+        self.positions.push(position);
+        self.next_position_id - 1
+    }
+    pub fn unlock(&mut self, position_id: u64, index: &mut RewardIndex) -> Option<LockPosition> {
+        let i = self
+            .positions
+            .iter()
+            .position(|position| position.id == position_id)?;
+        self.update_reward(index);
+        let position = self.positions.remove(i);
+        self.total_stake -= position.amount;
+        self.update_shares(self.shares - position.shares(), index);
+        Some(position)
+    }
 }
 //Call Methods
 #[near_bindgen]
 impl Contract {
     #[payable]
-    pub fn stake(&mut self, amount: u128) -> Promise {
+    pub fn stake(&mut self, amount: u128, lock_tier: Option<u8>) -> Promise {
         assert_eq!(env::attached_deposit(), ONE_YOCTO * 2);
-        assert_eq!(
-            amount <= self.token.ft_balance_of(env::signer_account_id()).0,
-            true,
+        self.assert_not_paused(Feature::Staking);
+        require!(!self.emergency_mode, "Staking is closed in emergency mode");
+        let account_id = env::predecessor_account_id();
+        self.internal_upgrade_stake(&account_id);
+        assert!(
+            amount <= self.token.ft_balance_of(account_id.clone()).0,
             "Insufficient NEKO balance for staking"
         );
-        assert_eq!(amount >= 100, true, "Minimum stake is 100 NEKO");
+        assert!(amount >= 100, "Minimum stake is 100 NEKO");
+        if let Some(tier) = lock_tier {
+            self.internal_get_lock_tier(tier);
+        }
         //process fee
-        let fee = (amount * self.fee_percent as u128)
-            .checked_div(100)
-            .unwrap();
+        let fee = self.config.bake_fee(amount);
 
         let amount_after_fee = amount - fee;
+        //Hold the NEKO in this contract until the factory has baked the cookies
+        self.token
+            .internal_transfer(&account_id, &env::current_account_id(), amount, None);
 
         ext_factory_contract::checked_bake(
-            env::signer_account_id(),
+            account_id.clone(),
             amount_after_fee,
             fee,
-            self.factory_id.clone(),
+            self.config.factory_id.clone(),
             ONE_YOCTO,
             env::prepaid_gas() / 3,
         )
         .then(ext_self::neko_stake_call_back(
-            env::signer_account_id(),
+            account_id,
             amount,
             fee,
+            lock_tier,
             env::current_account_id(),
             ONE_YOCTO,
             env::prepaid_gas() / 3,
@@ -76,28 +213,46 @@ impl Contract {
     #[payable]
     pub fn claim_cookie(&mut self) -> Promise {
         assert_one_yocto();
-        let claim_reward_fee = self.fee_percent;
-
-        let reward_before_fee = self
+        self.assert_not_paused(Feature::Claiming);
+        require!(!self.emergency_mode, "Claiming is closed in emergency mode");
+        let account_id = env::predecessor_account_id();
+        self.update_stake_data(&account_id);
+        let mut stake_data = self
             .stake
-            .get(&env::signer_account_id())
-            .unwrap_or_else(|| panic!("No stake record found for this account"))
-            .acc_reward;
-        let total_fee = (reward_before_fee * (claim_reward_fee as u128))
-            .checked_div(100)
-            .unwrap_or_else(|| panic!("Divide overflow check fail"));
+            .get(&account_id)
+            .unwrap_or_else(|| panic!("No stake record found for this account"));
+        let reward_before_fee = stake_data.acc_reward;
+        let total_fee = self.config.bake_fee(reward_before_fee);
         let reward_after_fee = reward_before_fee - total_fee;
+        //Settle the claim up front, the callback gives it back if the transfer fails
+        stake_data.acc_reward = 0;
+        self.stake.insert(&account_id, &stake_data);
+        if total_fee > 0 {
+            FeeCharged {
+                account_id: &account_id,
+                action: "claim",
+                amount: &U128(total_fee),
+            }
+            .emit();
+        }
+        Claim {
+            account_id: &account_id,
+            amount: &U128(reward_after_fee),
+        }
+        .emit();
 
         //external transfer cookie
         ext_factory_contract::ft_transfer(
-            env::signer_account_id(),
+            account_id.clone(),
             U128(reward_after_fee as u128),
             None,
-            self.factory_id.clone(),
+            self.config.factory_id.clone(),
             ONE_YOCTO,
             env::prepaid_gas() / 3,
         )
         .then(ext_self::claim_reward_call_back(
+            account_id,
+            reward_before_fee,
             env::current_account_id(),
             0,
             env::prepaid_gas() / 3,
@@ -106,31 +261,55 @@ impl Contract {
     #[payable]
     pub fn unstake(&mut self, amount: u128) -> Promise {
         assert_one_yocto();
-        /* assert!(
-            self.token.ft_balance_of(env::current_account_id()).0 >= amount,
-            "Insufficient NEKO balance in contract"
-        ); */
+        self.assert_not_paused(Feature::Unstaking);
+        require!(
+            !self.emergency_mode,
+            "Unstaking is closed in emergency mode, use emergency_withdraw"
+        );
+        let account_id = env::predecessor_account_id();
+        self.internal_upgrade_stake(&account_id);
+        let stake_data = self
+            .stake
+            .get(&account_id)
+            .unwrap_or_else(|| panic!("No stake record found for this account"));
+        require!(
+            amount <= stake_data.flexible_stake(),
+            "Insufficient unlocked stake"
+        );
+        //Take the stake off up front, the callback gives it back if the exchange fails
+        self.update_stake_decrease(&account_id, amount);
 
         ext_factory_contract::checked_exchange(
             amount,
-            self.factory_id.clone(),
+            self.config.factory_id.clone(),
             ONE_YOCTO,
             env::prepaid_gas() / 3,
         )
         .then(ext_self::cookie_exchange_call_back(
+            account_id,
+            amount,
             env::current_account_id(),
             ONE_YOCTO,
             env::prepaid_gas() / 3,
         ))
     }
     #[private]
-    pub fn claim_reward_call_back(&mut self) {
-        let mut stake_data = self
-            .stake
-            .get(&env::signer_account_id())
-            .unwrap_or_else(|| panic!("No stake record found for this account"));
-        stake_data.acc_reward = 0;
-        self.stake.insert(&env::signer_account_id(), &stake_data);
+    pub fn claim_reward_call_back(&mut self, account_id: AccountId, reward: Balance) {
+        if is_promise_success() {
+            return;
+        }
+        //Transfer failed, give the reward back
+        if let Some(mut stake_data) = self.stake.get(&account_id) {
+            stake_data.acc_reward += reward;
+            self.stake.insert(&account_id, &stake_data);
+        }
+        StakeFailed {
+            account_id: &account_id,
+            action: "claim",
+            amount: &U128(reward),
+            memo: Some("Reward restored"),
+        }
+        .emit();
     }
     #[private]
     #[payable]
@@ -139,95 +318,144 @@ impl Contract {
         to: AccountId,
         amount: Balance,
         fee: Balance,
-        #[callback] val: Balance,
+        lock_tier: Option<u8>,
     ) {
-        assert_eq!(to, env::signer_account_id(), "Error signer");
-
-        let amount_after_fee = val;
-        self.token.internal_transfer(
-            &env::signer_account_id(),
-            &env::current_account_id(),
-            amount,
-            None,
-        );
+        if promise_result_balance().is_none() {
+            //Bake failed, refund the NEKO held for this stake
+            self.token
+                .internal_transfer(&env::current_account_id(), &to, amount, None);
+            StakeFailed {
+                account_id: &to,
+                action: "stake",
+                amount: &U128(amount),
+                memo: Some("NEKO refunded"),
+            }
+            .emit();
+            return;
+        }
+        //The NEKO left in this contract after the fee backs the stake and the reserve alike
+        let amount_after_fee = amount - fee;
         //Burn the fee ( in NEKO) which will be Minted in Cookie
-        self.token
-            .internal_withdraw(&env::current_account_id(), fee);
-        //update Account Stake Data and Increase Total Stake
-        if let Some(_stake_data) = self.stake.get(&env::signer_account_id()) {
-            self.update_stake_increase(&env::signer_account_id(), amount_after_fee)
-        } else {
-            let stake_data = Stake {
-                total_stake: amount_after_fee,
-                acc_reward: 0,
-                last_update_time: env::block_timestamp(),
+        if fee > 0 {
+            self.token
+                .internal_burn(&env::current_account_id(), fee, Some("Bake fee"));
+            FeeCharged {
+                account_id: &to,
+                action: "stake",
+                amount: &U128(fee),
+            }
+            .emit();
+        }
+        self.staking_reserve += amount_after_fee;
+        //update Account Stake Data and Increase Total Stake, a tier removed in
+        //the meantime falls back to flexible stake
+        let position_id =
+            match lock_tier.filter(|tier| (*tier as usize) < self.config.lock_tiers.len()) {
+                Some(tier) => Some(self.update_stake_lock(&to, amount_after_fee, tier)),
+                None => {
+                    self.update_stake_increase(&to, amount_after_fee);
+                    None
+                }
             };
-            self.stake.insert(&env::signer_account_id(), &stake_data);
+        Stake {
+            account_id: &to,
+            amount: &U128(amount_after_fee),
+            position_id,
         }
+        .emit();
+        self.internal_compound(&to);
     }
     #[private]
     #[payable]
-    pub fn cookie_exchange_call_back(&mut self, #[callback] exchange_amount: Balance) {
-        if self.token.ft_balance_of(env::current_account_id()).0 < exchange_amount {
-            self.token
-                .internal_deposit(&env::current_account_id(), exchange_amount)
+    pub fn cookie_exchange_call_back(&mut self, account_id: AccountId, amount: Balance) {
+        if promise_result_balance().is_none() {
+            //Exchange failed, the cookies are left untouched and the stake is restored
+            self.update_stake_increase(&account_id, amount);
+            StakeFailed {
+                account_id: &account_id,
+                action: "unstake",
+                amount: &U128(amount),
+                memo: Some("Stake restored"),
+            }
+            .emit();
+            return;
         }
-        self.token.internal_transfer(
-            &env::current_account_id(),
-            &env::signer_account_id(),
-            exchange_amount,
-            None,
-        );
-        if let Some(stake_data) = self.stake.get(&env::signer_account_id()) {
-            self.update_stake_decrease(
-                &env::signer_account_id(),
-                std::cmp::min(exchange_amount, stake_data.total_stake),
-            );
+        //Only the NEKO taken off the stake is paid out, whatever the exchange returned
+        self.internal_redeem(&account_id, amount);
+        Unstake {
+            account_id: &account_id,
+            amount: &U128(amount),
+            position_id: None,
         }
+        .emit();
+        self.internal_compound(&account_id);
     }
 }
 
 #[near_bindgen]
 impl Contract {
     pub fn update_stake_data(&mut self, account_id: &AccountId) {
+        self.internal_upgrade_stake(account_id);
         if let Some(mut stake_data) = self.stake.get(account_id) {
-            let reward_add = stake_data.cal_reward(self.cookie_reward_rate as u128);
+            self.checkpoint_reward();
+            let reward_add = stake_data.update_reward(&self.reward_index);
             env::log_str(format!("reward added:{}", reward_add).as_str());
             if reward_add > 0 {
-                stake_data.acc_reward += reward_add;
-                stake_data.last_update_time = env::block_timestamp();
                 self.stake.insert(account_id, &stake_data);
             }
         } else {
             panic!("None stake data found for this account");
         }
     }
-    fn update_stake_increase(&mut self, account_id: &AccountId, amount: Balance) {
+    pub(crate) fn update_stake_increase(&mut self, account_id: &AccountId, amount: Balance) {
+        self.checkpoint_reward();
+        let mut stake_data = self.stake.get(account_id).unwrap_or_default();
+        stake_data.deposit(amount, &mut self.reward_index);
+        self.stake.insert(account_id, &stake_data);
+        self.total_staked += amount;
+    }
+    fn update_stake_lock(&mut self, account_id: &AccountId, amount: Balance, tier: u8) -> u64 {
+        let tier = self.internal_get_lock_tier(tier);
+        self.checkpoint_reward();
+        let mut stake_data = self.stake.get(account_id).unwrap_or_default();
+        let position_id = stake_data.lock(amount, &tier, &mut self.reward_index);
+        self.stake.insert(account_id, &stake_data);
+        self.total_staked += amount;
+        position_id
+    }
+    fn update_stake_decrease(&mut self, account_id: &AccountId, amount: Balance) {
         if let Some(mut stake_data) = self.stake.get(account_id) {
-            let reward_add = stake_data.cal_reward(self.cookie_reward_rate as u128);
-            env::log_str(format!("reward added:{}", reward_add).as_str());
-            /*    if reward_add > 0 { */
-            stake_data.acc_reward += reward_add;
-            stake_data.last_update_time = env::block_timestamp();
-            /*    } */
-            stake_data.total_stake += amount;
+            self.checkpoint_reward();
+            stake_data.withdraw(amount, &mut self.reward_index);
             self.stake.insert(account_id, &stake_data);
+            self.total_staked -= amount;
         } else {
-            panic!("None stake data found for this account");
+            env::log_str(format!("Convert Without Stake added:{}", amount).as_str());
         }
     }
-    fn update_stake_decrease(&mut self, account_id: &AccountId, amount: Balance) {
-        if let Some(mut stake_data) = self.stake.get(account_id) {
-            let reward_add = stake_data.cal_reward(self.cookie_reward_rate as u128);
+}
 
-            /*   if reward_add > 0 { */
-            stake_data.acc_reward += reward_add;
-            stake_data.last_update_time = env::block_timestamp();
-            /*   } */
-            stake_data.total_stake -= amount;
-            self.stake.insert(account_id, &stake_data);
+/// Balance returned by the promise a callback is attached to, `None` when it failed.
+pub(crate) fn promise_result_balance() -> Option<Balance> {
+    match env::promise_result(0) {
+        PromiseResult::Successful(value) => serde_json::from_slice::<Balance>(&value).ok(),
+        _ => None,
+    }
+}
+
+impl Contract {
+    /// Sends unstaked NEKO to `account_id`, through the unbonding queue when enabled.
+    pub(crate) fn internal_release_unstaked(&mut self, account_id: &AccountId, amount: Balance) {
+        if self.config.unbonding_period > 0 {
+            //Hold the NEKO in this contract until the unbonding period ends
+            self.internal_queue_unbonding(account_id, amount);
         } else {
-            env::log_str(format!("Convert Without Stake added:{}", amount).as_str());
+            self.token
+                .internal_transfer(&env::current_account_id(), account_id, amount, None);
         }
     }
+    /// Brings the global reward index up to date with the emission schedule.
+    pub(crate) fn checkpoint_reward(&mut self) {
+        self.emission.checkpoint(&mut self.reward_index);
+    }
 }
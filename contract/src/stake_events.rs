@@ -0,0 +1,182 @@
+//! Staking events, logged in the nep-297 format under their own standard so indexers
+//! can rebuild the stake of every account from them:
+//! <https://github.com/near/NEPs/blob/master/specs/Standards/EventsFormat.md>
+
+use near_sdk::json_types::U128;
+use near_sdk::{env, AccountId};
+use serde::Serialize;
+
+pub const NEKO_STAKE_EVENT_STANDARD: &str = "neko_stake";
+pub const NEKO_STAKE_EVENT_VERSION: &str = "1.0.0";
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+enum StakeEventKind<'a> {
+    Stake(&'a [Stake<'a>]),
+    Unstake(&'a [Unstake<'a>]),
+    Claim(&'a [Claim<'a>]),
+    Compound(&'a [Compound<'a>]),
+    FeeCharged(&'a [FeeCharged<'a>]),
+    RateChanged(&'a [RateChanged]),
+    EmergencyWithdraw(&'a [EmergencyWithdraw<'a>]),
+    StakeFailed(&'a [StakeFailed<'a>]),
+    NftStake(&'a [NftStake<'a>]),
+    NftUnstake(&'a [NftStake<'a>]),
+}
+
+#[derive(Serialize, Debug)]
+struct StakeEvent<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event_kind: StakeEventKind<'a>,
+}
+
+fn emit(event_kind: StakeEventKind) {
+    let event = StakeEvent {
+        standard: NEKO_STAKE_EVENT_STANDARD,
+        version: NEKO_STAKE_EVENT_VERSION,
+        event_kind,
+    };
+    // Events cannot fail to serialize so fine to panic on error
+    let json = serde_json::to_string(&event).unwrap_or_else(|_| env::abort());
+    env::log_str(&format!("EVENT_JSON:{}", json));
+}
+
+/// Data to log when `amount` was added to the stake of `account_id`, into lock
+/// position `position_id` if it was locked.
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct Stake<'a> {
+    pub account_id: &'a AccountId,
+    pub amount: &'a U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position_id: Option<u64>,
+}
+impl Stake<'_> {
+    pub fn emit(self) {
+        emit(StakeEventKind::Stake(&[self]))
+    }
+}
+
+/// Data to log when `amount` was removed from the stake of `account_id`, out of
+/// lock position `position_id` if it was locked.
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct Unstake<'a> {
+    pub account_id: &'a AccountId,
+    pub amount: &'a U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position_id: Option<u64>,
+}
+impl Unstake<'_> {
+    pub fn emit(self) {
+        emit(StakeEventKind::Unstake(&[self]))
+    }
+}
+
+/// Data to log when `account_id` claimed `amount` of its accrued reward.
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct Claim<'a> {
+    pub account_id: &'a AccountId,
+    pub amount: &'a U128,
+}
+impl Claim<'_> {
+    pub fn emit(self) {
+        emit(StakeEventKind::Claim(&[self]))
+    }
+}
+
+/// Data to log when accrued cookie `reward` was compounded into `amount` of additional stake.
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct Compound<'a> {
+    pub account_id: &'a AccountId,
+    pub reward: &'a U128,
+    pub amount: &'a U128,
+}
+impl Compound<'_> {
+    pub fn emit(self) {
+        emit(StakeEventKind::Compound(&[self]))
+    }
+}
+
+/// Data to log when `account_id` was charged `amount` on `action`.
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct FeeCharged<'a> {
+    pub account_id: &'a AccountId,
+    pub action: &'a str,
+    pub amount: &'a U128,
+}
+impl FeeCharged<'_> {
+    pub fn emit(self) {
+        emit(StakeEventKind::FeeCharged(&[self]))
+    }
+}
+
+/// Data to log for each reward rate epoch that was set, `reward_rate` applies from
+/// `start_time` on.
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RateChanged {
+    pub start_time: u64,
+    pub reward_rate: u8,
+}
+impl RateChanged {
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+    pub fn emit_many(data: &[RateChanged]) {
+        emit(StakeEventKind::RateChanged(data))
+    }
+}
+
+/// Data to log when `account_id` withdrew its stake `amount` in emergency mode,
+/// giving up `forfeited_reward`.
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct EmergencyWithdraw<'a> {
+    pub account_id: &'a AccountId,
+    pub amount: &'a U128,
+    pub forfeited_reward: &'a U128,
+}
+impl EmergencyWithdraw<'_> {
+    pub fn emit(self) {
+        emit(StakeEventKind::EmergencyWithdraw(&[self]))
+    }
+}
+
+/// Data to log when the factory call behind a stake `action` failed and was rolled back.
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct StakeFailed<'a> {
+    pub account_id: &'a AccountId,
+    pub action: &'a str,
+    pub amount: &'a U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+impl StakeFailed<'_> {
+    pub fn emit(self) {
+        emit(StakeEventKind::StakeFailed(&[self]))
+    }
+}
+
+/// Data to log when `account_id` staked or unstaked `token_id` of `collection_id`.
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct NftStake<'a> {
+    pub account_id: &'a AccountId,
+    pub collection_id: &'a AccountId,
+    pub token_id: &'a String,
+}
+impl NftStake<'_> {
+    pub fn emit_staked(self) {
+        emit(StakeEventKind::NftStake(&[self]))
+    }
+    pub fn emit_unstaked(self) {
+        emit(StakeEventKind::NftUnstake(&[self]))
+    }
+}
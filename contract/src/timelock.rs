@@ -0,0 +1,154 @@
+use super::*;
+use crate::access::Role;
+use crate::events::TimelockChange;
+use serde::{Deserialize, Serialize};
+
+/// Parameter change that only applies once its timelock has passed.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamChange {
+    RewardRate(u8),
+    /// Bake fee in basis points.
+    BakeFee(u16),
+    Vault(AccountId),
+    /// Changes the minimum delay of changes queued afterwards.
+    TimelockDelay(u64),
+    /// Replaces the emission schedule, the emitted total is kept.
+    EmissionSchedule {
+        epochs: Vec<Epoch>,
+        halving_interval: u64,
+        max_emission: Option<U128>,
+    },
+    /// Early exit penalty of lock positions in basis points, `None` disables early exits.
+    EarlyExitPenalty(Option<u16>),
+    TaxPolicy(TaxPolicy),
+    /// Bounds every later bake fee and reward rate change is validated against.
+    ConfigBounds {
+        max_bake_fee_bps: u16,
+        max_reward_rate: u8,
+    },
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Debug, Clone)]
+pub struct QueuedChange {
+    pub id: u64,
+    pub change: ParamChange,
+    /// Earliest block timestamp the change can be executed at.
+    pub execute_after: u64,
+}
+
+//Call Methods
+#[near_bindgen]
+impl Contract {
+    /// Queues `change` to be executed from `execute_after` on, which must be at least
+    /// the timelock delay away. Returns the id of the queued change.
+    pub fn queue_change(&mut self, change: ParamChange, execute_after: u64) -> u64 {
+        self.assert_no_multisig();
+        self.assert_can_change(&change);
+        self.internal_queue_change(change, execute_after)
+    }
+    pub fn execute_change(&mut self, id: u64) {
+        self.assert_no_multisig();
+        self.assert_can_change(&self.internal_get_queued_change(id).change);
+        self.internal_execute_change(id);
+    }
+    pub fn cancel_change(&mut self, id: u64) {
+        self.assert_no_multisig();
+        self.assert_can_change(&self.internal_get_queued_change(id).change);
+        self.internal_cancel_change(id);
+    }
+}
+
+impl Contract {
+    /// The bake fee and the early exit penalty belong to the fee managers, every
+    /// other change to the owner.
+    fn assert_can_change(&self, change: &ParamChange) {
+        match change {
+            ParamChange::BakeFee(_) | ParamChange::EarlyExitPenalty(_) => {
+                self.assert_role(Role::FeeManager)
+            }
+            _ => self.assert_owner_predecessor(),
+        }
+    }
+    pub(crate) fn internal_queue_change(&mut self, change: ParamChange, execute_after: u64) -> u64 {
+        require!(
+            execute_after >= env::block_timestamp() + self.timelock_delay,
+            "Execution time is before the timelock delay"
+        );
+        match &change {
+            ParamChange::RewardRate(rate) => self.config.assert_reward_rate(*rate),
+            ParamChange::BakeFee(bake_fee_bps) => self.config.assert_bake_fee(*bake_fee_bps),
+            ParamChange::EmissionSchedule {
+                epochs,
+                halving_interval,
+                max_emission,
+            } => {
+                self.internal_emission_schedule(epochs.clone(), *halving_interval, *max_emission);
+            }
+            ParamChange::EarlyExitPenalty(penalty_bps) => {
+                Config::assert_early_exit_penalty(*penalty_bps)
+            }
+            ParamChange::TaxPolicy(policy) => policy.assert_valid(),
+            ParamChange::ConfigBounds {
+                max_bake_fee_bps,
+                max_reward_rate,
+            } => self.assert_config_bounds(*max_bake_fee_bps, *max_reward_rate),
+            _ => {}
+        }
+        let queued = QueuedChange {
+            id: self.next_change_id,
+            change,
+            execute_after,
+        };
+        self.next_change_id += 1;
+        self.queued_changes.insert(&queued.id, &queued);
+        TimelockChange { change: &queued }.emit_queued();
+        queued.id
+    }
+    pub(crate) fn internal_execute_change(&mut self, id: u64) {
+        let queued = self.internal_get_queued_change(id);
+        require!(
+            env::block_timestamp() >= queued.execute_after,
+            "Timelock has not passed yet"
+        );
+        self.queued_changes.remove(&id);
+        match queued.change.clone() {
+            ParamChange::RewardRate(rate) => self.internal_set_reward_rate(rate),
+            ParamChange::BakeFee(bake_fee_bps) => {
+                //The bounds may have changed since the change was queued
+                self.config.assert_bake_fee(bake_fee_bps);
+                self.config.bake_fee_bps = bake_fee_bps;
+            }
+            ParamChange::Vault(vault_id) => self.internal_update_vault(vault_id),
+            ParamChange::TimelockDelay(delay) => self.timelock_delay = delay,
+            ParamChange::EmissionSchedule {
+                epochs,
+                halving_interval,
+                max_emission,
+            } => self.internal_set_emission_schedule(epochs, halving_interval, max_emission),
+            ParamChange::EarlyExitPenalty(penalty_bps) => {
+                Config::assert_early_exit_penalty(penalty_bps);
+                self.config.early_exit_penalty_bps = penalty_bps;
+            }
+            ParamChange::TaxPolicy(policy) => {
+                policy.assert_valid();
+                self.token.tax_policy = policy;
+            }
+            ParamChange::ConfigBounds {
+                max_bake_fee_bps,
+                max_reward_rate,
+            } => self.internal_set_config_bounds(max_bake_fee_bps, max_reward_rate),
+        }
+        TimelockChange { change: &queued }.emit_executed();
+    }
+    pub(crate) fn internal_cancel_change(&mut self, id: u64) {
+        let queued = self.internal_get_queued_change(id);
+        self.queued_changes.remove(&id);
+        TimelockChange { change: &queued }.emit_cancelled();
+    }
+    fn internal_get_queued_change(&self, id: u64) -> QueuedChange {
+        self.queued_changes
+            .get(&id)
+            .unwrap_or_else(|| panic!("No queued change found for this id"))
+    }
+}
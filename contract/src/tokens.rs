@@ -1,6 +1,36 @@
 use crate::constants::MAX_SUPPLY;
 
 use super::*;
+use near_contract_standards::fungible_token::events::FtMint;
+use near_sdk::json_types::I128;
+use serde::Serialize;
+
+/// Running state of `ft_audit_supply`, `balance_sum` covers the accounts before `next_index`.
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct SupplyAudit {
+    pub next_index: u64,
+    pub balance_sum: Balance,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SupplyAuditReport {
+    pub next_index: u64,
+    pub accounts: u64,
+    pub balance_sum: U128,
+    pub total_supply: U128,
+    /// Sum of balances minus total supply, only meaningful once `complete`.
+    pub difference: I128,
+    pub complete: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SupplyStats {
+    pub total_supply: U128,
+    pub total_burned: U128,
+    pub total_taxed: U128,
+    pub total_to_vault: U128,
+    pub total_to_stakers: U128,
+}
 #[near_bindgen]
 impl Contract {
     pub(crate) fn ft_internal_mint(&mut self, to: &AccountId, amount: Balance) {
@@ -10,12 +40,117 @@ impl Contract {
             if self.token.accounts.get(to).is_some() {
                 self.token.internal_deposit(to, amount);
             } else {
-                self.token.internal_register_account(&to);
+                self.token.internal_register_account(to);
                 self.token.internal_deposit(to, amount);
             }
         }
+        FtMint {
+            owner_id: to,
+            amount: &U128(amount),
+            memo: None,
+        }
+        .emit();
+    }
+    /// Adds the staker share of the tax taken since `total_to_stakers` to the staking reserve.
+    fn internal_credit_staker_tax(&mut self, total_to_stakers: Balance) {
+        self.staking_reserve += self.token.total_to_stakers - total_to_stakers;
     }
+    /// Adds the balances of the next `limit` registered accounts to the running audit,
+    /// `from` 0 starts a new audit and any other value must continue the last one.
+    pub fn ft_audit_supply(&mut self, from: u64, limit: u64) -> SupplyAuditReport {
+        self.assert_owner_predecessor();
+        if from == 0 {
+            self.supply_audit = SupplyAudit::default();
+        } else {
+            require!(
+                from == self.supply_audit.next_index,
+                "Audit must continue from next_index"
+            );
+        }
+        let (balance_sum, read) = self.token.internal_sum_balances(from, limit);
+        self.supply_audit.next_index += read;
+        self.supply_audit.balance_sum += balance_sum;
+
+        let accounts = self.token.account_index.len();
+        SupplyAuditReport {
+            next_index: self.supply_audit.next_index,
+            accounts,
+            balance_sum: U128(self.supply_audit.balance_sum),
+            total_supply: U128(self.token.total_supply),
+            difference: I128(
+                self.supply_audit.balance_sum as i128 - self.token.total_supply as i128,
+            ),
+            complete: self.supply_audit.next_index >= accounts,
+        }
+    }
+    /// Adds accounts registered before the migration to the index `ft_audit_supply`
+    /// walks, returns the number of accounts added.
+    pub fn ft_index_accounts(&mut self, account_ids: Vec<AccountId>) -> u64 {
+        self.assert_owner_predecessor();
+        require!(
+            account_ids.len() <= MAX_MAINTENANCE_BATCH,
+            "Too many accounts for one batch"
+        );
+        self.token.internal_index_accounts(&account_ids)
+    }
+    /// Mints NEKO out of the allowance of the calling minter.
     pub fn ft_mint(&mut self, to: AccountId, amount: Balance) {
+        self.assert_not_paused(Feature::Minting);
+        self.assert_no_multisig();
+        self.internal_use_minter_allowance(amount);
         self.ft_internal_mint(&to, amount);
     }
 }
+
+#[near_bindgen]
+impl FungibleTokenCore for Contract {
+    #[payable]
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
+        self.assert_not_paused(Feature::Transfers);
+        let total_to_stakers = self.token.total_to_stakers;
+        self.token.ft_transfer(receiver_id, amount, memo);
+        self.internal_credit_staker_tax(total_to_stakers);
+    }
+
+    #[payable]
+    fn ft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        self.assert_not_paused(Feature::Transfers);
+        let total_to_stakers = self.token.total_to_stakers;
+        let result = self.token.ft_transfer_call(receiver_id, amount, memo, msg);
+        self.internal_credit_staker_tax(total_to_stakers);
+        result
+    }
+
+    fn ft_total_supply(&self) -> U128 {
+        self.token.ft_total_supply()
+    }
+
+    fn ft_balance_of(&self, account_id: AccountId) -> U128 {
+        self.token.ft_balance_of(account_id)
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenResolver for Contract {
+    #[private]
+    fn ft_resolve_transfer(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> U128 {
+        let (used_amount, burned_amount) =
+            self.token
+                .internal_ft_resolve_transfer(&sender_id, receiver_id, amount);
+        if burned_amount > 0 {
+            self.on_tokens_burned(sender_id, burned_amount);
+        }
+        used_amount.into()
+    }
+}
@@ -0,0 +1,54 @@
+use super::*;
+use near_sdk::assert_one_yocto;
+use serde::Serialize;
+
+/// NEKO released by `unstake` that can be withdrawn once `release_time` has passed.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Debug, Clone)]
+pub struct UnbondingEntry {
+    pub amount: Balance,
+    pub release_time: u64,
+}
+
+//Call Methods
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn withdraw_unbonded(&mut self) -> U128 {
+        assert_one_yocto();
+        self.assert_not_paused(Feature::Unstaking);
+        let account_id = env::predecessor_account_id();
+        let entries = self
+            .unbonding
+            .get(&account_id)
+            .unwrap_or_else(|| panic!("No unbonding entries found for this account"));
+        let (matured, pending): (Vec<UnbondingEntry>, Vec<UnbondingEntry>) = entries
+            .into_iter()
+            .partition(|entry| entry.release_time <= env::block_timestamp());
+        let amount: Balance = matured.iter().map(|entry| entry.amount).sum();
+        require!(amount > 0, "No matured unbonding entries");
+
+        if pending.is_empty() {
+            self.unbonding.remove(&account_id);
+        } else {
+            self.unbonding.insert(&account_id, &pending);
+        }
+        self.token.internal_transfer(
+            &env::current_account_id(),
+            &account_id,
+            amount,
+            Some("Withdraw unbonded".to_string()),
+        );
+        U128(amount)
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_queue_unbonding(&mut self, account_id: &AccountId, amount: Balance) {
+        let mut entries = self.unbonding.get(account_id).unwrap_or_default();
+        entries.push(UnbondingEntry {
+            amount,
+            release_time: env::block_timestamp() + self.config.unbonding_period,
+        });
+        self.unbonding.insert(account_id, &entries);
+    }
+}
@@ -0,0 +1,42 @@
+use super::*;
+use near_contract_standards::upgrade::Upgradable;
+use near_sdk::Promise;
+
+#[derive(Serialize, Debug)]
+pub struct StagedCode {
+    pub sha256: Base64VecU8,
+    /// Anyone can deploy the code from this timestamp on.
+    pub deploy_after: u64,
+}
+
+//Call Methods
+#[near_bindgen]
+impl Contract {
+    /// Stages `code` for deployment at `timestamp`, which must be past the staging duration.
+    pub fn stage_code(&mut self, code: Base64VecU8, timestamp: u64) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.upgrade.internal_stage_code(code.into(), timestamp);
+    }
+    pub fn cancel_staged_code(&mut self) {
+        self.assert_owner_predecessor();
+        self.assert_no_multisig();
+        self.upgrade.internal_cancel_code();
+    }
+    /// Deploys the staged code and migrates the state in the same receipt,
+    /// a failing migration reverts the deployment.
+    pub fn deploy_code(&mut self) -> Promise {
+        self.upgrade
+            .deploy_code()
+            .function_call("migrate".to_string(), vec![], 0, GAS_FOR_MIGRATE)
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_staged_code(&self) -> Option<StagedCode> {
+        self.upgrade.staged_code_hash().map(|sha256| StagedCode {
+            sha256: sha256.into(),
+            deploy_after: self.upgrade.staging_timestamp,
+        })
+    }
+}
@@ -1,17 +1,193 @@
 use super::*;
+use crate::multisig::Proposal;
+use crate::nft_stake::StakedNft;
+use crate::pool::PoolView;
+use crate::reserve::ReserveView;
+use crate::tokens::SupplyStats;
+use crate::upgrade::StagedCode;
 
 #[near_bindgen]
 impl Contract {
+    pub fn get_owner(&self) -> Option<AccountId> {
+        self.owner_id.clone()
+    }
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner_id.clone()
+    }
     pub fn get_reward_rate(&self) -> u8 {
-        self.cookie_reward_rate
+        self.emission.rate_at(env::block_timestamp())
+    }
+    pub fn get_emission_schedule(&self) -> EmissionSchedule {
+        self.emission.clone()
+    }
+    pub fn get_config(&self) -> Config {
+        self.config.clone()
+    }
+    pub fn get_supply_stats(&self) -> SupplyStats {
+        SupplyStats {
+            total_supply: U128(self.token.total_supply),
+            total_burned: U128(self.token.total_burned),
+            total_taxed: U128(self.token.total_taxed),
+            total_to_vault: U128(self.token.total_to_vault),
+            total_to_stakers: U128(self.token.total_to_stakers),
+        }
+    }
+    pub fn get_tax_policy(&self) -> TaxPolicy {
+        self.token.tax_policy.clone()
+    }
+    pub fn get_tax_exempt_senders(&self, from_index: u64, limit: u64) -> Vec<AccountId> {
+        self.token
+            .tax_exemptions
+            .senders
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+    pub fn get_tax_exempt_receivers(&self, from_index: u64, limit: u64) -> Vec<AccountId> {
+        self.token
+            .tax_exemptions
+            .receivers
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
     }
-    pub fn get_fee_rate(&self) -> u8 {
-        self.fee_percent
+    pub fn get_custom_tax_rates(&self, from_index: u64, limit: u64) -> Vec<(AccountId, u16)> {
+        self.token
+            .tax_exemptions
+            .custom_rates
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
     }
     //Get Stake Data
-    pub fn get_stake_by_id(&self, id: AccountId) -> Stake {
-        self.stake
-            .get(&id)
-            .unwrap_or_else(|| panic!("No stake data found for this account"))
+    pub fn get_stake_by_id(&self, id: AccountId) -> StakeV2 {
+        let mut stake_data = self
+            .internal_stake_of(&id)
+            .unwrap_or_else(|| panic!("No stake data found for this account"));
+        stake_data.acc_reward +=
+            stake_data.pending_reward(self.emission.accumulated(&self.reward_index));
+        stake_data
+    }
+    pub fn get_reward_index(&self) -> RewardIndex {
+        self.reward_index.clone()
+    }
+    pub fn get_reserve(&self) -> ReserveView {
+        self.internal_reserve_view()
+    }
+    //Get Unbonding Data
+    pub fn get_unbonding(&self, account_id: AccountId) -> Vec<UnbondingEntry> {
+        self.unbonding.get(&account_id).unwrap_or_default()
+    }
+    //Get Timelock Data
+    pub fn get_timelock_delay(&self) -> u64 {
+        self.timelock_delay
+    }
+    /// Pending parameter changes by queue order, so users can react before they apply.
+    pub fn get_queued_changes(&self, from_index: u64, limit: u64) -> Vec<QueuedChange> {
+        self.queued_changes
+            .values()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+    //Get Multisig Data
+    pub fn get_multisig_signers(&self) -> Vec<AccountId> {
+        self.multisig.signers.to_vec()
+    }
+    pub fn get_multisig_threshold(&self) -> u32 {
+        self.multisig.threshold
+    }
+    pub fn get_proposal(&self, id: u64) -> Option<Proposal> {
+        self.multisig.proposals.get(&id)
+    }
+    pub fn get_proposals(&self, from_index: u64, limit: u64) -> Vec<Proposal> {
+        self.multisig
+            .proposals
+            .values()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+    //Get Upgrade Data
+    pub fn get_staging_duration(&self) -> u64 {
+        self.upgrade.staging_duration
+    }
+    pub fn get_staged_code(&self) -> Option<StagedCode> {
+        self.internal_staged_code()
+    }
+    pub fn is_maintenance_mode(&self) -> bool {
+        self.maintenance_mode
+    }
+    pub fn is_emergency_mode(&self) -> bool {
+        self.emergency_mode
+    }
+    //Get Pause Data
+    pub fn get_pause_flags(&self) -> PauseFlags {
+        self.paused.clone()
+    }
+    pub fn is_paused(&self, feature: Feature) -> bool {
+        self.paused.is_paused(feature)
+    }
+    //Get Access Data
+    pub fn get_roles(&self, account_id: AccountId) -> Vec<Role> {
+        self.roles.get(&account_id).unwrap_or_default()
+    }
+    pub fn has_role(&self, account_id: AccountId, role: Role) -> bool {
+        self.internal_has_role(&account_id, role)
+    }
+    pub fn get_minter_allowance(&self, account_id: AccountId) -> U128 {
+        U128(self.minter_allowances.get(&account_id).unwrap_or(0))
+    }
+    //Get Pool Data
+    pub fn get_pools(&self, from_index: u64, limit: u64) -> Vec<PoolView> {
+        self.pools
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .map(|(token_id, pool)| PoolView::from_pool(token_id, &pool))
+            .collect()
+    }
+    pub fn get_pool(&self, token_id: AccountId) -> PoolView {
+        let pool = self.internal_get_pool(&token_id);
+        PoolView::from_pool(token_id, &pool)
+    }
+    pub fn get_pool_stake(&self, token_id: AccountId, account_id: AccountId) -> StakeV2 {
+        let pool = self.internal_get_pool(&token_id);
+        let mut stake_data = pool
+            .stake
+            .get(&account_id)
+            .unwrap_or_else(|| panic!("No stake data found for this account"));
+        stake_data.acc_reward +=
+            stake_data.pending_reward(pool.reward_index.accumulated(pool.reward_rate as u128));
+        stake_data
+    }
+    //Get NFT Stake Data
+    pub fn get_nft_collections(
+        &self,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<(AccountId, NftCollection)> {
+        self.nft_collections
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+    pub fn get_nft_staked(&self, account_id: AccountId) -> Vec<StakedNft> {
+        self.nft_stake
+            .get(&account_id)
+            .map(|staker| staker.tokens)
+            .unwrap_or_default()
+    }
+    pub fn get_nft_stake_by_id(&self, account_id: AccountId) -> NftStaker {
+        let mut staker = self
+            .nft_stake
+            .get(&account_id)
+            .unwrap_or_else(|| panic!("No NFT stake data found for this account"));
+        self.update_nft_reward(&mut staker);
+        staker
     }
 }
@@ -22,8 +22,8 @@ const factory_methods = {
 	changeMethods: ["init_factory", "ft_mint"],
 };
 const contractMethods = {
-	viewMethods: ["get_fee_rate"],
-	changeMethods: ["set_bake_fee", "new_default_meta"],
+	viewMethods: ["get_config"],
+	changeMethods: ["queue_change", "execute_change", "new_default_meta"],
 };
 let config;
 let masterAccount;
@@ -97,7 +97,7 @@ async function test() {
 			owner_id: "alice.test.near",
 			vault_id: "vault.test.near",
 			factory_id: "factory.test.near",
-			fee_percent: 10,
+			bake_fee_bps: 1000,
 			cookie_reward_rate: 10,
 		},
 	});
@@ -108,9 +108,9 @@ async function test() {
 			vault_id: "vault.test.near",
 		},
 	});
-	const fee = await aliceUseContract.get_fee_rate({});
+	const config = await aliceUseContract.get_config({});
 	const factory_balance = await aliceUseFactory.ft_balance_of({ account_id: "alice.test.near" });
-	assert.equal(fee, 10);
+	assert.equal(config.bake_fee_bps, 1000);
 	console.log("Factory Balance:", factory_balance);
 }
 
@@ -60,7 +60,7 @@ async function main() {
 				owner_id: ACCOUNT_ID,
 				vault_id: VAULT_ID,
 				factory_id: "factory.nekocoin.testnet",
-				fee_percent: 5,
+				bake_fee_bps: 500,
 				cookie_reward_rate: 1,
 			});
 			break;
@@ -82,10 +82,19 @@ async function main() {
 			console.log(result_mint);
 			break;
 		case "update-vault":
-			const result_transfer = await contract.update_vault({
-				args: { vault_id: process.argv[3] },
+			const result_queue = await contract.queue_change({
+				args: {
+					change: { vault: process.argv[3] },
+					execute_after: parseInt(process.argv[4]),
+				},
+			});
+			console.log("Queued change:", result_queue);
+			break;
+		case "execute-change":
+			const result_execute = await contract.execute_change({
+				args: { id: parseInt(process.argv[3]) },
 			});
-			console.log(result_transfer);
+			console.log(result_execute);
 			break;
 		case "stake":
 			const stakeAmount = 100;
@@ -115,7 +124,7 @@ async function initNekoContract(contractId) {
 	const account = await near.account(ACCOUNT_ID);
 	const methodOptions = {
 		viewMethods: ["ft_balance_of", "ft_metadata"],
-		changeMethods: ["new_default_meta", "ft_mint", "ft_transfer", "update_vault", "stake", "clean"],
+		changeMethods: ["new_default_meta", "ft_mint", "ft_transfer", "queue_change", "execute_change", "stake", "clear_storage"],
 	};
 	return new Contract(account, contractId, methodOptions);
 }